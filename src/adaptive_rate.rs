@@ -0,0 +1,175 @@
+// src/adaptive_rate.rs
+
+use crate::error::Result;
+use crate::rate_limiter::RateLimiter;
+use crate::transport::TransportResponse;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Bounds and step sizes of an `AdaptiveRateController`.
+#[derive(Clone, Debug)]
+pub struct AdaptiveRate {
+    /// The lowest rate the controller backs off to, in requests per second.
+    pub min_rate: f64,
+    /// The highest rate the controller probes up to, in requests per second.
+    pub max_rate: f64,
+    /// How much the rate grows after a run of successes, in requests per second.
+    pub increase: f64,
+    /// The factor the rate is multiplied by when Polygon throttles us.
+    pub decrease_factor: f64,
+    /// How many successes in a row it takes before the rate grows.
+    pub successes_before_increase: u32,
+    /// How long after a decrease further throttled responses are ignored, so a burst of 429s
+    /// from requests sent at the old rate only lowers it once.
+    pub cooldown: Duration,
+}
+
+impl AdaptiveRate {
+    pub fn new(min_rate: f64, max_rate: f64) -> Self {
+        AdaptiveRate { min_rate, max_rate, ..AdaptiveRate::default() }
+    }
+
+    pub fn increase(mut self, increase: f64) -> Self {
+        self.increase = increase;
+        self
+    }
+
+    pub fn decrease_factor(mut self, decrease_factor: f64) -> Self {
+        self.decrease_factor = decrease_factor;
+        self
+    }
+
+    pub fn successes_before_increase(mut self, successes: u32) -> Self {
+        self.successes_before_increase = successes;
+        self
+    }
+
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+impl Default for AdaptiveRate {
+    fn default() -> Self {
+        AdaptiveRate {
+            min_rate: 1.0,
+            max_rate: 100.0,
+            increase: 1.0,
+            decrease_factor: 0.5,
+            successes_before_increase: 50,
+            cooldown: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Why the controller changed the rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateChangeReason {
+    /// Polygon answered 429 or asked us to wait with `Retry-After`.
+    Throttled,
+    /// A run of successful responses.
+    Probe,
+}
+
+impl fmt::Display for RateChangeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateChangeReason::Throttled => f.write_str("throttled"),
+            RateChangeReason::Probe => f.write_str("probe"),
+        }
+    }
+}
+
+/// A change of the session's request rate, in requests per second.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateChange {
+    pub previous_rate: f64,
+    pub new_rate: f64,
+    pub reason: RateChangeReason,
+}
+
+/// Adjusts a `RateLimiter` from the responses it lets through, additive increase and
+/// multiplicative decrease style.
+#[derive(Debug)]
+pub struct AdaptiveRateController {
+    settings: AdaptiveRate,
+    state: Mutex<ControllerState>,
+}
+
+#[derive(Debug, Default)]
+struct ControllerState {
+    successes: u32,
+    last_decrease: Option<Instant>,
+    changes: Vec<RateChange>,
+}
+
+impl AdaptiveRateController {
+    pub fn new(settings: AdaptiveRate) -> Self {
+        AdaptiveRateController { settings, state: Mutex::new(ControllerState::default()) }
+    }
+
+    pub fn settings(&self) -> &AdaptiveRate {
+        &self.settings
+    }
+
+    /// Moves the limiter within the configured bounds, e.g. when it starts outside them.
+    pub(crate) fn clamp(&self, rate_limiter: &RateLimiter) {
+        let rate = rate_limiter.rate().clamp(self.settings.min_rate, self.settings.max_rate);
+        rate_limiter.set_rate(rate);
+    }
+
+    /// Updates the limiter from a response: throttled responses lower the rate and pause the
+    /// limiter for any `Retry-After`, successes raise it once enough have been seen in a row.
+    ///
+    /// Only fails if a shared limiter's file cannot be written.
+    pub async fn observe(&self, response: &TransportResponse, rate_limiter: &RateLimiter) -> Result<()> {
+        // Pausing may wait on a shared limiter's file lock, so it happens before taking the state lock
+        if let Some(retry_after) = response.retry_after {
+            rate_limiter.pause(retry_after).await?;
+        }
+        let throttled = response.status == 429 || response.retry_after.is_some();
+        let mut state = self.state.lock().unwrap();
+
+        if throttled {
+            state.successes = 0;
+            let cooling_down = state
+                .last_decrease
+                .is_some_and(|last_decrease| last_decrease.elapsed() < self.settings.cooldown);
+            if !cooling_down {
+                state.last_decrease = Some(Instant::now());
+                let rate = rate_limiter.rate().min(self.settings.max_rate) * self.settings.decrease_factor;
+                self.change(&mut state, rate_limiter, rate, RateChangeReason::Throttled);
+            }
+        } else if response.is_success() {
+            state.successes += 1;
+            if state.successes >= self.settings.successes_before_increase {
+                state.successes = 0;
+                let rate = rate_limiter.rate() + self.settings.increase;
+                self.change(&mut state, rate_limiter, rate, RateChangeReason::Probe);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns every rate change so far, oldest first.
+    pub fn changes(&self) -> Vec<RateChange> {
+        self.state.lock().unwrap().changes.clone()
+    }
+
+    /// Returns the rate changes after the first `skip`.
+    pub(crate) fn changes_since(&self, skip: usize) -> Vec<RateChange> {
+        self.state.lock().unwrap().changes.iter().skip(skip).cloned().collect()
+    }
+
+    fn change(&self, state: &mut ControllerState, rate_limiter: &RateLimiter, rate: f64, reason: RateChangeReason) {
+        let previous_rate = rate_limiter.rate();
+        let new_rate = rate.clamp(self.settings.min_rate, self.settings.max_rate);
+        if new_rate != previous_rate {
+            rate_limiter.set_rate(new_rate);
+            state.changes.push(RateChange { previous_rate, new_rate, reason });
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// src/agg_extractor.rs
+
+use super::agg_query::{SortOrder, MAX_LIMIT};
+use super::data_extractor::AggDataExtractor;
+use super::extract_options::ExtractOptions;
+use super::poly_agg_info::PolyAggInfo;
+use super::session::PolygonHistorySession;
+use std::sync::Arc;
+
+/// Extracts aggregate bars of any Polygon timespan, using the resolution and
+/// multiplier carried by `PolyAggInfo`.
+pub struct AggExtractor {
+    pub extractor: AggDataExtractor,
+}
+
+impl AggExtractor {
+    pub fn new(poly_agg_info: PolyAggInfo, session: Arc<PolygonHistorySession>) -> Self {
+        let data_extractor = AggDataExtractor {
+            poly_agg_info,
+            adjusted: true,
+            sort: SortOrder::Asc,
+            limit: MAX_LIMIT,
+            options: ExtractOptions::default(),
+            checkpoints: None,
+            session,
+        };
+        AggExtractor { extractor: data_extractor }
+    }
+}
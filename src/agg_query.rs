@@ -0,0 +1,108 @@
+// src/agg_query.rs
+
+use crate::poly_agg_info::Timespan;
+use chrono::NaiveDate;
+use std::fmt;
+
+/// The maximum number of bars Polygon returns in one aggregates page.
+pub const MAX_LIMIT: u32 = 50_000;
+
+/// The order Polygon sorts aggregate bars in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One request to the Polygon aggregates endpoint.
+///
+/// The query travels through the request, retry and response stages as is, and is only
+/// rendered to a URL when it is sent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AggQuery {
+    pub ticker: String,
+    pub multiplier: u32,
+    pub timespan: Timespan,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub adjusted: bool,
+    pub sort: SortOrder,
+    pub limit: u32,
+}
+
+impl AggQuery {
+    /// Creates a query for adjusted bars in ascending order, with the largest page size.
+    pub fn new(ticker: &str, multiplier: u32, timespan: Timespan, from: NaiveDate, to: NaiveDate) -> Self {
+        AggQuery {
+            ticker: ticker.to_string(),
+            multiplier,
+            timespan,
+            from,
+            to,
+            adjusted: true,
+            sort: SortOrder::Asc,
+            limit: MAX_LIMIT,
+        }
+    }
+
+    pub fn adjusted(mut self, adjusted: bool) -> Self {
+        self.adjusted = adjusted;
+        self
+    }
+
+    pub fn sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Returns the requested date, or `from..to` for a multi-day window.
+    pub fn date_label(&self) -> String {
+        if self.from == self.to {
+            self.from.format("%Y-%m-%d").to_string()
+        } else {
+            format!("{}..{}", self.from.format("%Y-%m-%d"), self.to.format("%Y-%m-%d"))
+        }
+    }
+
+    /// Renders the query as a path relative to the Polygon base URL, percent-encoding the ticker.
+    pub fn to_url(&self) -> String {
+        format!(
+            "/v2/aggs/ticker/{}/range/{}/{}/{}/{}?adjusted={}&sort={}&limit={}",
+            urlencoding::encode(&self.ticker),
+            self.multiplier,
+            self.timespan,
+            self.from.format("%Y-%m-%d"),
+            self.to.format("%Y-%m-%d"),
+            self.adjusted,
+            self.sort,
+            self.limit
+        )
+    }
+}
+
+impl fmt::Display for AggQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_url())
+    }
+}
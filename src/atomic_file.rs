@@ -0,0 +1,36 @@
+// src/atomic_file.rs
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Numbers the temporary files written by this process.
+static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// Writes `path` through a temporary file next to it, renamed into place once `write` succeeds, so
+/// an interrupted run never leaves a truncated file. Other tasks and processes may write the same
+/// path, so every writer gets a temporary file of its own.
+pub(crate) fn write_atomically(path: &Path, write: impl FnOnce(File) -> std::io::Result<()>) -> std::io::Result<()> {
+    let temporary_path = temporary_path(path);
+    let result = File::create(&temporary_path)
+        .and_then(write)
+        .and_then(|_| std::fs::rename(&temporary_path, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary_path);
+    }
+    result
+}
+
+/// Writes `contents` to `path` like `write_atomically`, off the runtime's threads.
+pub(crate) async fn write_bytes_atomically(path: PathBuf, contents: Vec<u8>) -> std::io::Result<()> {
+    tokio::task::spawn_blocking(move || write_atomically(&path, |mut file| file.write_all(&contents)))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.{}.tmp", std::process::id(), TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)));
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,146 @@
+// src/cache.rs
+
+use crate::agg_query::AggQuery;
+use crate::atomic_file::write_bytes_atomically;
+use crate::error::{Error, ErrorContext, Result};
+use crate::processor::MarketTimezone;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long responses covering the current trading day are reused by default.
+pub const DEFAULT_OPEN_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How a `ResponseCache` is used by the session.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Serve fresh entries from disk and store every new response.
+    #[default]
+    ReadWrite,
+    /// Serve fresh entries from disk but never write to it.
+    ReadOnly,
+    /// Serve every entry, however old, and fail instead of sending requests for missing ones.
+    Offline,
+    /// Ignore the cache entirely.
+    Bypass,
+}
+
+/// A cache of raw Polygon aggregate responses on disk.
+///
+/// Entries are keyed by the normalized `AggQuery`, which never contains the API key, and hold
+/// every page of the query. Entries whose window ended before today in the market timezone are
+/// immutable and never expire; entries that include today expire after the open TTL.
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    mode: CacheMode,
+    open_ttl: Duration,
+}
+
+/// The file stored for a single query.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    /// Seconds since the Unix epoch at which the pages were fetched.
+    fetched_at: u64,
+    pages: Vec<String>,
+}
+
+impl ResponseCache {
+    /// Creates a read-write cache in the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ResponseCache { dir: dir.into(), mode: CacheMode::default(), open_ttl: DEFAULT_OPEN_TTL }
+    }
+
+    pub fn with_mode(mut self, mode: CacheMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets how long entries that include the current trading day are reused.
+    pub fn with_open_ttl(mut self, open_ttl: Duration) -> Self {
+        self.open_ttl = open_ttl;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// Returns the file an entry for the query is stored in.
+    pub fn path(&self, query: &AggQuery) -> PathBuf {
+        self.dir
+            .join(urlencoding::encode(&query.ticker.to_ascii_uppercase()).as_ref())
+            .join(format!("{}_{}", query.multiplier, query.timespan))
+            .join(format!(
+                "{}_{}_adjusted={}_sort={}_limit={}.json",
+                query.from.format("%Y-%m-%d"),
+                query.to.format("%Y-%m-%d"),
+                query.adjusted,
+                query.sort,
+                query.limit
+            ))
+    }
+
+    /// Returns the cached page bodies of the query, if there is a usable entry.
+    ///
+    /// In offline mode every entry is usable and a missing one is an `Error::Cache`.
+    pub async fn load(&self, query: &AggQuery) -> Result<Option<Vec<String>>> {
+        if self.mode == CacheMode::Bypass {
+            return Ok(None);
+        }
+
+        let entry = match tokio::fs::read(self.path(query)).await {
+            Ok(bytes) => serde_json::from_slice::<CacheEntry>(&bytes).ok(),
+            Err(_) => None,
+        };
+
+        match entry {
+            Some(entry) if self.mode == CacheMode::Offline || self.is_fresh(query, &entry) => Ok(Some(entry.pages)),
+            _ if self.mode == CacheMode::Offline => Err(self.error(query, "No cached response for this query in offline mode")),
+            _ => Ok(None),
+        }
+    }
+
+    /// Stores the page bodies of the query. Does nothing unless the cache is read-write.
+    pub async fn store(&self, query: &AggQuery, pages: &[String]) -> Result<()> {
+        if self.mode != CacheMode::ReadWrite {
+            return Ok(());
+        }
+
+        let entry = CacheEntry { url: query.to_url(), fetched_at: unix_time(SystemTime::now()), pages: pages.to_vec() };
+        let json = serde_json::to_vec(&entry).map_err(|e| self.error(query, &e.to_string()))?;
+        let path = self.path(query);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| self.error(query, &e.to_string()))?;
+        }
+
+        write_bytes_atomically(path, json).await.map_err(|e| self.error(query, &e.to_string()))
+    }
+
+    /// Returns true if the entry covers closed trading days only, or is younger than the open TTL.
+    fn is_fresh(&self, query: &AggQuery, entry: &CacheEntry) -> bool {
+        let today = Utc::now().with_timezone(&MarketTimezone::Eastern.timezone()).date_naive();
+        if query.to < today {
+            return true;
+        }
+        let age = unix_time(SystemTime::now()).saturating_sub(entry.fetched_at);
+        Duration::from_secs(age) < self.open_ttl
+    }
+
+    fn error(&self, query: &AggQuery, message: &str) -> Error {
+        Error::Cache {
+            context: ErrorContext::new().ticker(&query.ticker).date(query.date_label()).url(query.to_url()),
+            message: message.to_string(),
+        }
+    }
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0)
+}
@@ -0,0 +1,219 @@
+// src/calendar.rs
+
+use crate::processor::MarketTimezone;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc, Weekday};
+
+/// Decides which dates a market is open on, so extraction does not request dates that can
+/// never have bars.
+pub trait TradingCalendar: Send + Sync {
+    /// Returns true if the market has a session on the given date.
+    fn is_trading_day(&self, date: NaiveDate) -> bool;
+
+    /// Returns the early closing time of the given date, if the session ends early.
+    fn early_close(&self, _date: NaiveDate) -> Option<NaiveTime> {
+        None
+    }
+
+    /// Returns every trading day between `start_date` and `end_date`, inclusive.
+    fn trading_days(&self, start_date: NaiveDate, end_date: NaiveDate) -> Vec<NaiveDate> {
+        let mut days = Vec::new();
+        let mut current_date = start_date;
+        while current_date <= end_date {
+            if self.is_trading_day(current_date) {
+                days.push(current_date);
+            }
+            current_date += ChronoDuration::days(1);
+        }
+        days
+    }
+
+    /// Returns the first trading day after the given date.
+    fn next_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut next_date = date + ChronoDuration::days(1);
+        while !self.is_trading_day(next_date) {
+            next_date += ChronoDuration::days(1);
+        }
+        next_date
+    }
+
+    /// Returns the last trading day before the given date.
+    fn previous_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut previous_date = date - ChronoDuration::days(1);
+        while !self.is_trading_day(previous_date) {
+            previous_date -= ChronoDuration::days(1);
+        }
+        previous_date
+    }
+}
+
+/// The NYSE calendar, which Nasdaq shares: weekends, the regular exchange holidays, one-off
+/// closures since 2001, and 1:00 PM early closes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NyseCalendar;
+
+/// A calendar with a session every day, for markets such as crypto that never close.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EveryDayCalendar;
+
+/// Full-day closures that do not follow the regular holiday rules.
+const NYSE_SPECIAL_CLOSURES: &[(i32, u32, u32)] = &[
+    (2001, 9, 11), // September 11 attacks
+    (2001, 9, 12),
+    (2001, 9, 13),
+    (2001, 9, 14),
+    (2004, 6, 11), // National day of mourning for Ronald Reagan
+    (2007, 1, 2),  // National day of mourning for Gerald Ford
+    (2012, 10, 29), // Hurricane Sandy
+    (2012, 10, 30),
+    (2018, 12, 5), // National day of mourning for George H.W. Bush
+    (2025, 1, 9),  // National day of mourning for Jimmy Carter
+];
+
+impl NyseCalendar {
+    /// Returns true if the exchange is closed for a holiday or special closure on the given date.
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        let year = date.year();
+        let special_closure = NYSE_SPECIAL_CLOSURES
+            .iter()
+            .any(|&(y, m, d)| NaiveDate::from_ymd_opt(y, m, d) == Some(date));
+
+        special_closure
+            || new_years_day_observed(year) == Some(date)
+            // Martin Luther King Jr. Day has been observed since 1998
+            || (year >= 1998 && date == nth_weekday(year, 1, Weekday::Mon, 3))
+            || date == nth_weekday(year, 2, Weekday::Mon, 3)
+            || date == easter_sunday(year) - ChronoDuration::days(2)
+            || date == last_weekday(year, 5, Weekday::Mon)
+            // Juneteenth has been observed since 2022
+            || (year >= 2022 && date == observed(ymd(year, 6, 19)))
+            || date == observed(ymd(year, 7, 4))
+            || date == nth_weekday(year, 9, Weekday::Mon, 1)
+            || date == nth_weekday(year, 11, Weekday::Thu, 4)
+            || date == observed(ymd(year, 12, 25))
+    }
+}
+
+impl TradingCalendar for NyseCalendar {
+    fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && !self.is_holiday(date)
+    }
+
+    fn early_close(&self, date: NaiveDate) -> Option<NaiveTime> {
+        if !self.is_trading_day(date) {
+            return None;
+        }
+
+        let year = date.year();
+        let early_close = (date == ymd(year, 7, 3) && date.weekday() != Weekday::Fri)
+            || date == nth_weekday(year, 11, Weekday::Thu, 4) + ChronoDuration::days(1)
+            || (date == ymd(year, 12, 24) && date.weekday() != Weekday::Fri);
+
+        if early_close {
+            NaiveTime::from_hms_opt(13, 0, 0)
+        } else {
+            None
+        }
+    }
+}
+
+impl TradingCalendar for EveryDayCalendar {
+    fn is_trading_day(&self, _date: NaiveDate) -> bool {
+        true
+    }
+}
+
+/// Returns the calendar to use for a Polygon ticker.
+///
+/// Crypto (`X:`) and forex (`C:`) tickers trade around the clock and use every day; stocks,
+/// options (`O:`) and indices (`I:`) follow the NYSE calendar.
+pub fn calendar_for_ticker(ticker: &str) -> &'static dyn TradingCalendar {
+    if trades_around_the_clock(ticker) {
+        &EveryDayCalendar
+    } else {
+        &NyseCalendar
+    }
+}
+
+/// Returns the latest trading day of the ticker whose session had closed at the given time.
+///
+/// Stock sessions close with the regular session, early closes included, in the market
+/// timezone; markets that trade around the clock close at midnight there, so their latest
+/// closed session is yesterday.
+pub fn latest_closed_session(ticker: &str, now: DateTime<Utc>) -> NaiveDate {
+    let market = MarketTimezone::Eastern;
+    let now = now.with_timezone(&market.timezone());
+    let today = now.date_naive();
+    let calendar = calendar_for_ticker(ticker);
+    if trades_around_the_clock(ticker) {
+        return calendar.previous_trading_day(today);
+    }
+
+    let close = calendar.early_close(today).unwrap_or(market.working_hours().1);
+    if calendar.is_trading_day(today) && now.time() >= close {
+        today
+    } else {
+        calendar.previous_trading_day(today)
+    }
+}
+
+/// Returns true for crypto and forex tickers, which have no sessions or holidays.
+pub(crate) fn trades_around_the_clock(ticker: &str) -> bool {
+    ticker.starts_with("X:") || ticker.starts_with("C:")
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Moves a holiday falling on a Saturday to the Friday before, and on a Sunday to the Monday after.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - ChronoDuration::days(1),
+        Weekday::Sun => date + ChronoDuration::days(1),
+        _ => date,
+    }
+}
+
+/// New Year's Day moves to Monday when it falls on a Sunday, but the exchange does not close
+/// on the preceding Friday when it falls on a Saturday.
+fn new_years_day_observed(year: i32) -> Option<NaiveDate> {
+    let new_years_day = ymd(year, 1, 1);
+    match new_years_day.weekday() {
+        Weekday::Sat => None,
+        _ => Some(observed(new_years_day)),
+    }
+}
+
+/// Returns the nth (1-based) given weekday of a month.
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+/// Returns the last given weekday of a month.
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// Computes Easter Sunday with the anonymous Gregorian algorithm.
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    ymd(year, month as u32, day as u32)
+}
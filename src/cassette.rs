@@ -0,0 +1,115 @@
+// src/cassette.rs
+
+use crate::error::{Error, ErrorContext, Result};
+use crate::transport::{strip_api_key, ResponseQueues, Transport, TransportResponse};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The request/response pairs of a recorded session, as stored in a cassette file.
+#[derive(Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Interaction {
+    /// The requested URL, without the `apiKey` parameter.
+    url: String,
+    status: u16,
+    body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_after_secs: Option<u64>,
+}
+
+/// Sends requests through another transport and records every response to a cassette file.
+///
+/// URLs are recorded with the `apiKey` parameter removed. Requests that fail before a response
+/// arrives are not recorded. Call `save` once the session is done to write the cassette.
+pub struct RecordingTransport {
+    inner: Arc<dyn Transport>,
+    path: PathBuf,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: Arc<dyn Transport>, path: impl Into<PathBuf>) -> Self {
+        RecordingTransport { inner, path: path.into(), interactions: Mutex::new(Vec::new()) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes every interaction recorded so far to the cassette file, replacing it.
+    pub fn save(&self) -> Result<()> {
+        let cassette = Cassette { interactions: self.interactions.lock().unwrap().clone() };
+        let cassette_error = |message: String| Error::Cassette {
+            context: ErrorContext::new().url(self.path.display().to_string()),
+            message,
+        };
+
+        let json = serde_json::to_string_pretty(&cassette).map_err(|e| cassette_error(e.to_string()))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| cassette_error(e.to_string()))?;
+        }
+        std::fs::write(&self.path, json).map_err(|e| cassette_error(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    async fn get(&self, url: &str) -> Result<TransportResponse> {
+        let response = self.inner.get(url).await?;
+        self.interactions.lock().unwrap().push(Interaction {
+            url: strip_api_key(url),
+            status: response.status,
+            body: response.body.clone(),
+            retry_after_secs: response.retry_after.map(|retry_after| retry_after.as_secs()),
+        });
+        Ok(response)
+    }
+}
+
+/// Serves the responses of a cassette file recorded by `RecordingTransport`.
+///
+/// Responses recorded for the same URL are served in the order they were recorded, and the last
+/// one is repeated. Requests that were never recorded fail with `Error::Cassette`, which is not
+/// retried.
+pub struct ReplayTransport {
+    responses: ResponseQueues,
+}
+
+impl ReplayTransport {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let cassette_error = |message: String| Error::Cassette {
+            context: ErrorContext::new().url(path.display().to_string()),
+            message,
+        };
+
+        let json = std::fs::read_to_string(path).map_err(|e| cassette_error(e.to_string()))?;
+        let cassette: Cassette = serde_json::from_str(&json).map_err(|e| cassette_error(e.to_string()))?;
+
+        let responses = ResponseQueues::default();
+        for interaction in cassette.interactions {
+            let mut response = TransportResponse::new(interaction.status, interaction.body);
+            response.retry_after = interaction.retry_after_secs.map(Duration::from_secs);
+            responses.push(&interaction.url, response);
+        }
+        Ok(ReplayTransport { responses })
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn get(&self, url: &str) -> Result<TransportResponse> {
+        let key = strip_api_key(url);
+        self.responses.next(&key).ok_or_else(|| Error::Cassette {
+            context: ErrorContext::new().url(key),
+            message: "No response recorded for this URL".to_string(),
+        })
+    }
+}
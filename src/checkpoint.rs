@@ -0,0 +1,127 @@
+// src/checkpoint.rs
+
+use crate::atomic_file::write_bytes_atomically;
+use crate::error::{Error, ErrorContext, Result};
+use crate::poly_agg_info::Timespan;
+use crate::processor::MarketTimezone;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Identifies the bars a manifest tracks: one ticker at one resolution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CheckpointKey {
+    pub ticker: String,
+    pub multiplier: u32,
+    pub timespan: Timespan,
+    pub adjusted: bool,
+}
+
+impl CheckpointKey {
+    pub fn new(ticker: &str, multiplier: u32, timespan: Timespan, adjusted: bool) -> Self {
+        CheckpointKey { ticker: ticker.to_string(), multiplier, timespan, adjusted }
+    }
+}
+
+/// Records which trading days of a backfill are done, so a rerun only fetches the rest.
+///
+/// Each ticker and resolution has a JSON manifest listing its completed dates. Only closed
+/// trading days are recorded: dates from today on in the market timezone are ignored, since
+/// their bars may still change. Manifests are replaced atomically, so an interrupted run never
+/// leaves a truncated one, and marking a date twice is harmless.
+#[derive(Clone, Debug)]
+pub struct CheckpointStore {
+    dir: PathBuf,
+    /// Serializes read-modify-write cycles on the manifests within this process.
+    lock: Arc<Mutex<()>>,
+}
+
+/// The file stored for a single key.
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    ticker: String,
+    multiplier: u32,
+    timespan: String,
+    adjusted: bool,
+    completed: Vec<String>,
+}
+
+impl CheckpointStore {
+    /// Creates a store keeping its manifests in the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CheckpointStore { dir: dir.into(), lock: Arc::new(Mutex::new(())) }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the manifest file of the key.
+    pub fn path(&self, key: &CheckpointKey) -> PathBuf {
+        self.dir
+            .join(urlencoding::encode(&key.ticker.to_ascii_uppercase()).as_ref())
+            .join(format!("{}_{}_adjusted={}.json", key.multiplier, key.timespan, key.adjusted))
+    }
+
+    /// Returns the dates recorded as completed for the key; none if it has no manifest yet.
+    pub async fn completed(&self, key: &CheckpointKey) -> Result<BTreeSet<NaiveDate>> {
+        let bytes = match tokio::fs::read(self.path(key)).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+            Err(e) => return Err(self.error(key, &e.to_string())),
+        };
+        let manifest: Manifest = serde_json::from_slice(&bytes).map_err(|e| self.error(key, &e.to_string()))?;
+        manifest
+            .completed
+            .iter()
+            .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| self.error(key, &e.to_string())))
+            .collect()
+    }
+
+    /// Adds the given dates to the key's manifest, skipping any that are not closed yet.
+    pub async fn mark_completed(&self, key: &CheckpointKey, dates: impl IntoIterator<Item = NaiveDate>) -> Result<()> {
+        let today = Utc::now().with_timezone(&MarketTimezone::Eastern.timezone()).date_naive();
+        let dates: Vec<NaiveDate> = dates.into_iter().filter(|date| *date < today).collect();
+        if dates.is_empty() {
+            return Ok(());
+        }
+
+        let _guard = self.lock.lock().await;
+        let mut completed = self.completed(key).await?;
+        completed.extend(dates);
+
+        let manifest = Manifest {
+            ticker: key.ticker.clone(),
+            multiplier: key.multiplier,
+            timespan: key.timespan.to_string(),
+            adjusted: key.adjusted,
+            completed: completed.iter().map(|date| date.format("%Y-%m-%d").to_string()).collect(),
+        };
+        let json = serde_json::to_vec_pretty(&manifest).map_err(|e| self.error(key, &e.to_string()))?;
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| self.error(key, &e.to_string()))?;
+        }
+
+        write_bytes_atomically(path, json).await.map_err(|e| self.error(key, &e.to_string()))
+    }
+
+    /// Forgets every completed date of the key, so the next run fetches the whole range again.
+    pub async fn clear(&self, key: &CheckpointKey) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        match tokio::fs::remove_file(self.path(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(self.error(key, &e.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    fn error(&self, key: &CheckpointKey, message: &str) -> Error {
+        Error::Checkpoint {
+            context: ErrorContext::new().ticker(&key.ticker).url(self.path(key).display().to_string()),
+            message: message.to_string(),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+// src/config.rs
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const API_KEY_ENV_VAR: &str = "POLYGON_API_KEY";
+pub const DEFAULT_BASE_URL: &str = "https://api.polygon.io";
+pub const DEFAULT_REQUESTS_PER_SECOND: u32 = 100;
+pub const DEFAULT_MAX_BURST_REQUESTS: u32 = 100;
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Runtime settings for a `PolygonHistorySession`.
+#[derive(Clone)]
+pub struct SessionConfig {
+    pub api_key: String,
+    pub base_url: String,
+    pub requests_per_second: u32,
+    pub max_burst_requests: u32,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+}
+
+impl SessionConfig {
+    pub fn builder() -> SessionConfigBuilder {
+        SessionConfigBuilder::default()
+    }
+
+    /// Builds a config with default settings and the API key taken from `POLYGON_API_KEY`.
+    pub fn from_env() -> Result<Self> {
+        SessionConfig::builder().build()
+    }
+
+    /// Builds a config from a TOML file, falling back to `POLYGON_API_KEY` if the file has no key.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        SessionConfig::builder().config_file(path).build()
+    }
+}
+
+impl fmt::Debug for SessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("api_key", &"<redacted>")
+            .field("base_url", &self.base_url)
+            .field("requests_per_second", &self.requests_per_second)
+            .field("max_burst_requests", &self.max_burst_requests)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .finish()
+    }
+}
+
+/// Builds a `SessionConfig`.
+///
+/// Explicitly set values take precedence over the config file, which takes precedence over
+/// the environment and the built-in defaults.
+#[derive(Default)]
+pub struct SessionConfigBuilder {
+    api_key: Option<String>,
+    config_file: Option<PathBuf>,
+    base_url: Option<String>,
+    requests_per_second: Option<u32>,
+    max_burst_requests: Option<u32>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+}
+
+impl SessionConfigBuilder {
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn config_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.config_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    pub fn requests_per_second(mut self, requests_per_second: u32) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    pub fn max_burst_requests(mut self, max_burst_requests: u32) -> Self {
+        self.max_burst_requests = Some(max_burst_requests);
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<SessionConfig> {
+        let file = match &self.config_file {
+            Some(path) => ConfigFile::load(path)?,
+            None => ConfigFile::default(),
+        };
+
+        let api_key = self
+            .api_key
+            .or(file.api_key)
+            .or_else(|| std::env::var(API_KEY_ENV_VAR).ok())
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| Error::Config {
+                message: format!("No Polygon API key configured; set {} or provide one explicitly", API_KEY_ENV_VAR),
+            })?;
+
+        let requests_per_second = self
+            .requests_per_second
+            .or(file.requests_per_second)
+            .unwrap_or(DEFAULT_REQUESTS_PER_SECOND);
+        if requests_per_second == 0 {
+            return Err(Error::Config { message: "requests_per_second must be greater than zero".to_string() });
+        }
+
+        let connect_timeout_secs = parse_timeout("connect_timeout_secs", file.connect_timeout_secs)?;
+        let request_timeout_secs = parse_timeout("request_timeout_secs", file.request_timeout_secs)?;
+
+        Ok(SessionConfig {
+            api_key,
+            base_url: self
+                .base_url
+                .or(file.base_url)
+                .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            requests_per_second,
+            max_burst_requests: self
+                .max_burst_requests
+                .or(file.max_burst_requests)
+                .unwrap_or(DEFAULT_MAX_BURST_REQUESTS)
+                .max(1),
+            connect_timeout: self
+                .connect_timeout
+                .or(connect_timeout_secs)
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+            request_timeout: self
+                .request_timeout
+                .or(request_timeout_secs)
+                .unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+        })
+    }
+}
+
+/// Converts a timeout read from a config file, rejecting negative, NaN and infinite values.
+fn parse_timeout(name: &str, secs: Option<f64>) -> Result<Option<Duration>> {
+    secs.map(|secs| {
+        Duration::try_from_secs_f64(secs)
+            .map_err(|_| Error::Config { message: format!("{} must be a finite, non-negative number, got {}", name, secs) })
+    })
+    .transpose()
+}
+
+/// The on-disk TOML representation of a `SessionConfig`. Every field is optional.
+#[derive(Default, Deserialize)]
+struct ConfigFile {
+    api_key: Option<String>,
+    base_url: Option<String>,
+    requests_per_second: Option<u32>,
+    max_burst_requests: Option<u32>,
+    connect_timeout_secs: Option<f64>,
+    request_timeout_secs: Option<f64>,
+}
+
+impl ConfigFile {
+    fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(|e| Error::Config {
+            message: format!("Unable to read config file {}: {}", path.display(), e),
+        })?;
+        toml::from_str(&contents).map_err(|e| Error::Config {
+            message: format!("Invalid config file {}: {}", path.display(), e),
+        })
+    }
+}
@@ -0,0 +1,69 @@
+// src/csv_sink.rs
+
+use crate::error::Result;
+use crate::file_sink::{DailyFiles, TimestampFormat};
+use crate::sink::Sink;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Writes processed bars to CSV files, one per ticker and day, laid out like `ParquetSink`'s
+/// partitions: `ticker=AAPL/year=2024/month=01/2024-01-02.csv`.
+#[derive(Clone, Debug)]
+pub struct CsvSink {
+    files: DailyFiles,
+    separator: u8,
+    include_header: bool,
+}
+
+impl CsvSink {
+    /// Creates a sink writing comma separated files with a header below the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CsvSink { files: DailyFiles::new(dir.into(), "csv"), separator: b',', include_header: true }
+    }
+
+    /// Sets how the `time` column is written. An invalid `Strftime` format fails every write.
+    pub fn timestamps(mut self, timestamps: TimestampFormat) -> Self {
+        self.files.timestamps = timestamps;
+        self
+    }
+
+    /// Sets the timezone formatted timestamps are written in. Defaults to UTC.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.files.timezone = timezone;
+        self
+    }
+
+    pub fn separator(mut self, separator: u8) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    pub fn include_header(mut self, include_header: bool) -> Self {
+        self.include_header = include_header;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.files.dir
+    }
+
+    /// Returns the file holding the ticker's bars of the given day.
+    pub fn path(&self, ticker: &str, mkt_date: NaiveDate) -> PathBuf {
+        self.files.path(ticker, mkt_date)
+    }
+}
+
+#[async_trait]
+impl Sink for CsvSink {
+    async fn write(&self, bars: &DataFrame) -> Result<()> {
+        let (include_header, separator) = (self.include_header, self.separator);
+        self.files
+            .write(bars, move |day, file| {
+                CsvWriter::new(file).include_header(include_header).with_separator(separator).finish(day)
+            })
+            .await
+    }
+}
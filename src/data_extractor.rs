@@ -1,103 +1,333 @@
 // src/data_extractor.rs
 
-use super::poly_agg_info::PolyAggInfo;
+use super::agg_query::{AggQuery, SortOrder};
+use super::calendar::calendar_for_ticker;
+use super::checkpoint::{CheckpointKey, CheckpointStore};
+use super::error::{Error, ErrorContext, Result};
+use super::extract_options::ExtractOptions;
+use super::poly_agg_info::{PolyAggInfo, Timespan};
+use super::processor::MarketTimezone;
+use super::report::{DateReport, DateStatus, ExtractionReport};
+use super::transport::TransportResponse;
 use super::PolygonHistorySession;
-use chrono::Duration as ChronoDuration;
-use futures::future::join_all;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate};
+use chrono_tz::Tz;
 use polars::prelude::*;
 use serde_json::{to_string, Value};
+use std::collections::{BTreeSet, HashSet, VecDeque};
 use std::io::Cursor;
 use std::sync::Arc;
 use std::time::Duration;
-use futures::StreamExt;
-use tokio::time::{Instant, sleep};
+use futures::stream::FuturesUnordered;
+use futures::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Instant};
+use tokio_util::sync::CancellationToken;
 
 /// Represents an aggregate data extractor for retrieving data from the Polygon API.
 pub struct AggDataExtractor {
     pub poly_agg_info: PolyAggInfo,
-    pub base_query: String,
-    pub limit: String,
+    /// Whether to request split adjusted bars.
+    pub adjusted: bool,
+    pub sort: SortOrder,
+    /// The page size of each request, at most `agg_query::MAX_LIMIT`.
+    pub limit: u32,
+    pub options: ExtractOptions,
+    /// Skips the dates this store has recorded as completed. Dates are only recorded by
+    /// `mark_completed`, once their bars have been kept.
+    pub checkpoints: Option<CheckpointStore>,
+    pub session: Arc<PolygonHistorySession>,
+}
+
+/// The bars of a single date, or of a multi-day window for coarse resolutions, as yielded by
+/// `AggDataExtractor::extract_stream`.
+pub struct DayBatch {
+    /// The requested date, or `start..end` for a multi-day window.
+    pub date: String,
+    /// The date's bars, including the `mkt_date` and `ticker` columns. Empty for dates without bars.
+    pub df: DataFrame,
+    pub report: DateReport,
 }
 
 impl AggDataExtractor{
     /// Extracts aggregate data from the Polygon API based on the provided information.
     ///
-    /// Returns a DataFrame containing the extracted aggregate data.
-    pub async fn extract(&self) -> Result<DataFrame, PolarsError> {
-        let date_range = DateRangeBuilder::create(&self.poly_agg_info);
-        let queries = QueryBuilder::build(&self.base_query, &self.poly_agg_info, &self.limit, &date_range);
+    /// Returns a DataFrame containing the extracted aggregate data, or an error if any date
+    /// could not be fetched.
+    pub async fn extract(&self) -> Result<DataFrame> {
+        self.extract_complete().await.map(|(combined_df, _)| combined_df)
+    }
 
-        let agg_data_schema = DataFrameBuilder::create_schema();
-        let mut combined_df = DataFrameBuilder::create_empty();
+    /// Like `extract`, but also returns the report, whose dates all succeeded or had no bars.
+    pub(crate) async fn extract_complete(&self) -> Result<(DataFrame, ExtractionReport)> {
+        let (combined_df, report, failures) = self.extract_all(CancellationToken::new()).await?;
+        match failures.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok((combined_df, report)),
+        }
+    }
 
-        let mut failed_requests = Vec::new();
+    /// Extracts aggregate data and reports the outcome of every requested date.
+    ///
+    /// Dates that still fail once the retries are used up are recorded as failed in the report
+    /// instead of failing the extraction. Errors that retrying cannot fix are still returned.
+    pub async fn extract_with_report(&self) -> Result<(DataFrame, ExtractionReport)> {
+        self.extract_with_cancellation(CancellationToken::new()).await
+    }
 
-        let mut response_stream = futures::stream::iter(queries)
-            .map(|query| {
-                let date = extract_date(&query);
-                async move {
-                    match PolygonHistorySession::send_request(&query).await {
-                        Ok(response) => (date, Ok(response)),
-                        Err(error) => (query, Err(error.to_string())),
-                    }
-                }
-            })
-            .buffer_unordered(100);
+    /// Like `extract_with_report`, but stops once `cancel` is cancelled, e.g. on Ctrl-C.
+    ///
+    /// Requests in flight are aborted and no new ones are sent. The bars fetched so far are
+    /// returned, and every date that did not finish is reported as cancelled so a later run can
+    /// fetch it.
+    pub async fn extract_with_cancellation(&self, cancel: CancellationToken) -> Result<(DataFrame, ExtractionReport)> {
+        let (combined_df, report, _) = self.extract_all(cancel).await?;
+        Ok((combined_df, report))
+    }
+
+    /// Extracts aggregate data one date at a time.
+    ///
+    /// Dates are yielded as soon as they are fetched, in no particular order, so callers can
+    /// write or process each day without holding the whole range in memory. Dates that exhaust
+    /// their retries are yielded as errors; after an error that retrying cannot fix, the stream ends.
+    ///
+    /// Nothing is checkpointed; call `mark_completed` with a batch's report once its bars are kept.
+    pub fn extract_stream(&self) -> impl Stream<Item = Result<DayBatch>> {
+        futures::stream::unfold(self.spawn_outcomes(CancellationToken::new()), |mut outcomes| async move {
+            outcomes.recv().await.map(|outcome| (outcome, outcomes))
+        })
+        .map(|outcome| {
+            let outcome = outcome?;
+            let df = outcome.result?.unwrap_or_default();
+            Ok(DayBatch { date: outcome.report.date.clone(), df, report: outcome.report })
+        })
+    }
+
+    /// Returns the key under which the extractor's completed dates are checkpointed.
+    pub fn checkpoint_key(&self) -> Result<CheckpointKey> {
+        QueryBuilder::new(self).checkpoint_key()
+    }
+
+    /// Records the trading days of the reported dates that succeeded or had no bars in the
+    /// checkpoint store, so later runs skip them. Does nothing without a checkpoint store.
+    ///
+    /// Call it only once the bars of those dates have been written somewhere that outlives the
+    /// run; dates recorded too early are never fetched again.
+    pub async fn mark_completed(&self, dates: &[DateReport]) -> Result<()> {
+        let Some(checkpoints) = &self.checkpoints else {
+            return Ok(());
+        };
+
+        let calendar = calendar_for_ticker(&self.poly_agg_info.ticker);
+        let mut completed = Vec::new();
+        for date in dates.iter().filter(|date| matches!(date.status, DateStatus::Succeeded | DateStatus::Empty)) {
+            let (from, to) = date.date.split_once("..").unwrap_or((&date.date, &date.date));
+            let parse = |label: &str| {
+                NaiveDate::parse_from_str(label, "%Y-%m-%d").map_err(|e| Error::InvalidInput {
+                    context: ErrorContext::new().ticker(&self.poly_agg_info.ticker).date(&date.date),
+                    message: e.to_string(),
+                })
+            };
+            completed.extend(calendar.trading_days(parse(from)?, parse(to)?));
+        }
+        checkpoints.mark_completed(&self.checkpoint_key()?, completed).await
+    }
+
+    /// Runs the extraction, returning the data, the report and the errors of the dates that
+    /// exhausted their retries.
+    async fn extract_all(&self, cancel: CancellationToken) -> Result<(DataFrame, ExtractionReport, Vec<Error>)> {
+        let mut combined_df = DataFrameBuilder::create_empty();
+        let mut report = ExtractionReport::new(&self.poly_agg_info.ticker);
+        let mut failures = Vec::new();
+        let earlier_rate_changes = self.session.rate_changes().len();
+
+        let mut outcomes = self.spawn_outcomes(cancel);
+        while let Some(outcome) = outcomes.recv().await {
+            let outcome = outcome?;
+            report.dates.push(outcome.report);
+            match outcome.result {
+                Ok(Some(df)) => DataFrameBuilder::combine(&mut combined_df, vec![df])?,
+                Ok(None) => {}
+                Err(error) => failures.push(error),
+            }
+        }
+
+        DataFrameBuilder::finalize(&mut combined_df).map_err(|source| Error::Schema {
+            context: ErrorContext::new().ticker(&self.poly_agg_info.ticker),
+            source,
+        })?;
+        report.sort();
+        report.rate_changes = self.session.rate_changes_since(earlier_rate_changes);
+        Ok((combined_df, report, failures))
+    }
 
-        while let Some(result) = response_stream.next().await {
-            match result {
-                (date, Ok(response)) => {
-                    if let Some(df) = ResponseProcessor::process_single((date, Ok(response)), &agg_data_schema).await? {
-                        DataFrameBuilder::combine(&mut combined_df, vec![df])?;
+    /// Starts fetching every date in the background and returns the channel its outcomes are
+    /// sent to. The channel is bounded, so fetching pauses while the consumer falls behind.
+    fn spawn_outcomes(&self, cancel: CancellationToken) -> mpsc::Receiver<Result<DayOutcome>> {
+        let query_builder = QueryBuilder::new(self);
+        let checkpoints = self.checkpoints.clone();
+        let session = Arc::clone(&self.session);
+        let ticker = self.poly_agg_info.ticker.clone();
+        let options = self.options.clone();
+
+        let (sender, receiver) = mpsc::channel(OUTCOME_BUFFER);
+        tokio::spawn(async move {
+            let result = match query_builder.build(checkpoints.as_ref()).await {
+                Ok(queries) => {
+                    let run = RequestSender::run(&session, &ticker, queries, &options, &cancel, &sender);
+                    match options.deadline {
+                        Some(deadline) => timeout(deadline, run).await.unwrap_or_else(|_| {
+                            Err(Error::DeadlineExceeded { context: ErrorContext::new().ticker(&ticker), deadline })
+                        }),
+                        None => run.await,
                     }
                 }
-                (query, Err(error)) => {
-                    failed_requests.push((query, error));
-                }
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                let _ = sender.send(Err(error)).await;
             }
-        }
+        });
+        receiver
+    }
+}
 
-        RequestSender::retry_failed(&failed_requests, &agg_data_schema, &mut combined_df).await?;
+/// How many fetched dates may wait for the consumer before fetching pauses.
+const OUTCOME_BUFFER: usize = 32;
 
-        DataFrameBuilder::finalize(&mut combined_df, &self.poly_agg_info.ticker)?;
+/// The final outcome of one date: its bars, no bars, the error that exhausted its retries, or
+/// no bars because the extraction was cancelled.
+struct DayOutcome {
+    report: DateReport,
+    result: Result<Option<DataFrame>>,
+}
 
-        Ok(combined_df)
-    }
+/// A query to send, and how many times it has been retried before.
+struct Attempt {
+    query: AggQuery,
+    retries: u32,
+}
+
+/// The result of fetching every page of one query.
+struct FetchedQuery {
+    query: AggQuery,
+    retries: u32,
+    pages: Result<Vec<Value>>,
+    latency: Duration,
+}
+
+/// A query whose last attempt failed and that will not be retried again.
+struct FailedRequest {
+    query: AggQuery,
+    error: Error,
+    latency: Duration,
+}
+
+/// The first and last trading day covered by one request.
+struct DateWindow {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
 }
 
 /// Builds a date range based on the provided PolyAggInfo.
 struct DateRangeBuilder;
 
 impl DateRangeBuilder {
-    /// Creates a vector of date strings based on the provided PolyAggInfo.
-    fn create(poly_agg_info: &PolyAggInfo) -> Vec<String> {
-        let mut date_range = Vec::new();
-        let mut current_date = poly_agg_info.start_date;
-        while current_date <= poly_agg_info.end_date {
-            date_range.push(current_date.format("%Y-%m-%d").to_string());
-            current_date += ChronoDuration::days(1);
+    /// Splits the trading days of the provided PolyAggInfo, except the completed ones, into
+    /// request windows.
+    ///
+    /// Second and minute bars are requested one trading day at a time. Coarser bars are
+    /// requested in windows of as many calendar days as fit in `limit` bars, so a long range of
+    /// daily bars takes a single request. A window never spans a completed day.
+    fn create(
+        poly_agg_info: &PolyAggInfo,
+        timespan: Timespan,
+        limit: u32,
+        completed: &BTreeSet<NaiveDate>,
+    ) -> Vec<DateWindow> {
+        let trading_days = calendar_for_ticker(&poly_agg_info.ticker)
+            .trading_days(poly_agg_info.start_date, poly_agg_info.end_date);
+        let window_days = DateRangeBuilder::window_days(timespan, poly_agg_info.multiplier, limit);
+
+        let mut windows: Vec<DateWindow> = Vec::new();
+        let mut window_open = false;
+        for date in trading_days {
+            if completed.contains(&date) {
+                window_open = false;
+                continue;
+            }
+            match windows.last_mut() {
+                Some(window) if window_open && date - window.start_date < ChronoDuration::days(window_days) => {
+                    window.end_date = date
+                }
+                _ => windows.push(DateWindow { start_date: date, end_date: date }),
+            }
+            window_open = true;
         }
-        date_range
+        windows
+    }
+
+    /// Returns how many calendar days a single request may cover without exceeding `limit` bars.
+    fn window_days(timespan: Timespan, multiplier: u32, limit: u32) -> i64 {
+        if matches!(timespan, Timespan::Second | Timespan::Minute) {
+            return 1;
+        }
+        let limit = i64::from(limit.max(1));
+        let bar_seconds = timespan.min_seconds() * i64::from(multiplier.max(1));
+
+        // A window of n days holds at most n * 86400 / bar_seconds + 1 bars
+        ((limit - 1).saturating_mul(bar_seconds) / ChronoDuration::days(1).num_seconds()).max(1)
     }
 }
 
-/// Builds queries based on the provided base query, PolyAggInfo, limit, and date range.
-struct QueryBuilder;
+/// Builds the queries of an extraction from a copy of the extractor's settings, so they can be
+/// built in the background.
+struct QueryBuilder {
+    poly_agg_info: PolyAggInfo,
+    adjusted: bool,
+    sort: SortOrder,
+    limit: u32,
+}
 
 impl QueryBuilder {
-    /// Builds a vector of query strings by replacing placeholders in the base query
-    /// with the corresponding values from PolyAggInfo, limit, and date range.
-    fn build(base_query: &str, poly_agg_info: &PolyAggInfo, limit: &str, date_range: &[String]) -> Vec<String> {
-        date_range
-            .iter()
-            .map(|date| {
-                base_query
-                    .replace("{ticker}", &poly_agg_info.ticker)
-                    .replace("{start_date}", date)
-                    .replace("{end_date}", date)
-                    .replace("{limit}", limit)
+    fn new(extractor: &AggDataExtractor) -> Self {
+        QueryBuilder {
+            poly_agg_info: extractor.poly_agg_info.clone(),
+            adjusted: extractor.adjusted,
+            sort: extractor.sort,
+            limit: extractor.limit,
+        }
+    }
+
+    /// Fails if the resolution is not a Polygon timespan.
+    fn checkpoint_key(&self) -> Result<CheckpointKey> {
+        let poly_agg_info = &self.poly_agg_info;
+        let timespan = poly_agg_info.timespan()?;
+        Ok(CheckpointKey::new(&poly_agg_info.ticker, poly_agg_info.multiplier, timespan, self.adjusted))
+    }
+
+    /// Builds one query per date window of the PolyAggInfo, leaving out the dates the checkpoint
+    /// store has recorded as completed.
+    ///
+    /// Fails if the resolution is not a Polygon timespan.
+    async fn build(&self, checkpoints: Option<&CheckpointStore>) -> Result<Vec<AggQuery>> {
+        let poly_agg_info = &self.poly_agg_info;
+        let timespan = poly_agg_info.timespan()?;
+        let completed = match checkpoints {
+            Some(checkpoints) => checkpoints.completed(&self.checkpoint_key()?).await?,
+            None => BTreeSet::new(),
+        };
+
+        let queries = DateRangeBuilder::create(poly_agg_info, timespan, self.limit, &completed)
+            .into_iter()
+            .map(|window| {
+                AggQuery::new(&poly_agg_info.ticker, poly_agg_info.multiplier, timespan, window.start_date, window.end_date)
+                    .adjusted(self.adjusted)
+                    .sort(self.sort)
+                    .limit(self.limit)
             })
-            .collect()
+            .collect();
+        Ok(queries)
     }
 }
 
@@ -105,124 +335,297 @@ impl QueryBuilder {
 struct RequestSender;
 
 impl RequestSender {
-    /// Sends requests to the Polygon API based on the provided queries.
+    /// Fetches every page of a query, timing the attempt.
+    async fn fetch(session: &PolygonHistorySession, attempt: Attempt, request_timeout: Option<Duration>) -> FetchedQuery {
+        let started = Instant::now();
+        let pages = RequestSender::fetch_pages(session, &attempt.query, request_timeout).await;
+        FetchedQuery { query: attempt.query, retries: attempt.retries, pages, latency: started.elapsed() }
+    }
+
+    /// Sends a query and follows Polygon's `next_url` cursor until every page has been fetched.
     ///
-    /// Returns a tuple containing the successful responses and failed requests.
-    async fn send(queries: Vec<String>) -> (Vec<(String, reqwest::Response)>, Vec<(String, String)>) {
-        let futures = queries.into_iter().map(|query| {
-            let date = extract_date(&query);
-            tokio::spawn(async move {
-                match PolygonHistorySession::send_request(&query).await {
-                    Ok(response) => (date, Ok(response)),
-                    Err(error) => (query, Err(error.to_string())),
-                }
-            })
-        });
+    /// Pages are served from the session's cache when it holds the query, and stored in it
+    /// once every page has been fetched. Each page request fails with `Error::Timeout` if it
+    /// takes longer than `request_timeout`. Returns the parsed JSON of each page, in order.
+    async fn fetch_pages(
+        session: &PolygonHistorySession,
+        query: &AggQuery,
+        request_timeout: Option<Duration>,
+    ) -> Result<Vec<Value>> {
+        let query_url = query.to_url();
+        if let Some(cache) = session.cache() {
+            if let Some(bodies) = cache.load(query).await? {
+                return bodies
+                    .into_iter()
+                    .map(|body| RequestSender::check_response(&query_url, TransportResponse::new(200, body)))
+                    .collect();
+            }
+        }
 
-        let results: Vec<_> = join_all(futures).await;
-        let (successful_responses, failed_requests): (Vec<_>, Vec<_>) = results
-            .into_iter()
-            .map(|result| result.unwrap())
-            .partition(|(_, result)| result.is_ok());
+        let mut pages = Vec::new();
+        let mut bodies = Vec::new();
+        let mut next_url = Some(query_url);
+
+        while let Some(url) = next_url {
+            let response = match request_timeout {
+                Some(request_timeout) => timeout(request_timeout, session.send_request(&url))
+                    .await
+                    .map_err(|_| Error::Timeout { context: ErrorContext::new().url(&url), timeout: request_timeout })??,
+                None => session.send_request(&url).await?,
+            };
+            let body = response.body.clone();
+            let json = RequestSender::check_response(&url, response)?;
+
+            next_url = json["next_url"].as_str().map(|next| next.to_string());
+            pages.push(json);
+            bodies.push(body);
+        }
 
-        let successful_responses: Vec<_> = successful_responses
-            .into_iter()
-            .map(|(date, result)| (date, result.unwrap()))
-            .collect();
+        if let Some(cache) = session.cache() {
+            cache.store(query, &bodies).await?;
+        }
+        Ok(pages)
+    }
 
-        let failed_requests: Vec<_> = failed_requests
-            .into_iter()
-            .map(|(date, result)| (date, result.unwrap_err()))
-            .collect();
+    /// Checks the HTTP status and the Polygon `status` field of a response and returns its JSON.
+    ///
+    /// 401 and 403 become `Error::Unauthorized`, which is never retried.
+    fn check_response(url: &str, response: TransportResponse) -> Result<Value> {
+        let context = ErrorContext::new().url(url);
+
+        if response.status == 401 || response.status == 403 {
+            let message = serde_json::from_str::<Value>(&response.body)
+                .map(|json| polygon_error_message(&json, &response.body))
+                .unwrap_or(response.body);
+            return Err(Error::Unauthorized { context, status: response.status, message });
+        }
 
-        (successful_responses, failed_requests)
+        if !response.is_success() {
+            return Err(Error::HttpStatus {
+                context,
+                status: response.status,
+                body: response.body,
+                retry_after: response.retry_after,
+            });
+        }
+
+        let json = serde_json::from_str::<Value>(&response.body).map_err(|e| Error::Decode {
+            context: context.clone(),
+            message: format!("Error parsing JSON ({}): {}", e, response.body),
+        })?;
+
+        match json["status"].as_str() {
+            Some("NOT_AUTHORIZED") => Err(Error::Unauthorized {
+                context,
+                status: response.status,
+                message: polygon_error_message(&json, &response.body),
+            }),
+            Some("ERROR") => Err(Error::Api {
+                context,
+                status: "ERROR".to_string(),
+                message: polygon_error_message(&json, &response.body),
+            }),
+            _ => Ok(json),
+        }
     }
 
-    /// Retries failed requests and updates the combined DataFrame with the successful responses.
-    async fn retry_failed(
-        failed_requests: &[(String, String)],
-        agg_data_schema: &Arc<Schema>,
-        combined_df: &mut DataFrame,
-    ) -> Result<(), PolarsError> {
-        let mut remaining_failed_requests = failed_requests.to_vec();
-        let mut retry_count = 0;
+    /// Fetches every query, sending each date's outcome as soon as it is known.
+    ///
+    /// At most `max_in_flight` queries are fetched at once. A query that fails with an error the
+    /// retry policy allows is set aside for its backoff, without holding a slot, and then queued
+    /// again ahead of the queries not yet sent. Once `cancel` is cancelled, the requests in flight
+    /// are dropped and every unfinished query is sent as cancelled. Returns early, without an
+    /// error, if the receiving side has been dropped.
+    async fn run(
+        session: &Arc<PolygonHistorySession>,
+        ticker: &str,
+        queries: Vec<AggQuery>,
+        options: &ExtractOptions,
+        cancel: &CancellationToken,
+        sender: &mpsc::Sender<Result<DayOutcome>>,
+    ) -> Result<()> {
+        let agg_data_schema = DataFrameBuilder::create_schema();
+        let retry_policy = &options.retry_policy;
+        let max_in_flight = options.max_in_flight.max(1);
 
-        while !remaining_failed_requests.is_empty() && retry_count < 5 {
-            let retry_queries: Vec<_> = remaining_failed_requests.iter().map(|(query, _)| query.clone()).collect();
-            let (successful_retries, new_failed_requests) = RequestSender::send(retry_queries).await;
+        let mut unfinished: HashSet<AggQuery> = queries.iter().cloned().collect();
 
-            let df_vec = ResponseProcessor::process_all(successful_retries, agg_data_schema).await?;
-            DataFrameBuilder::combine(combined_df, df_vec)?;
+        let mut queued: VecDeque<Attempt> = queries.into_iter().map(|query| Attempt { query, retries: 0 }).collect();
+        let mut in_flight = FuturesUnordered::new();
+        let mut backing_off = FuturesUnordered::new();
 
-            remaining_failed_requests = new_failed_requests;
-            retry_count += 1;
+        loop {
+            while in_flight.len() < max_in_flight {
+                match queued.pop_front() {
+                    Some(attempt) => in_flight.push(RequestSender::fetch(session, attempt, options.request_timeout)),
+                    None => break,
+                }
+            }
 
-            if !remaining_failed_requests.is_empty() {
-                let backoff_duration = Duration::from_secs(2u64.pow(retry_count));
-                sleep(backoff_duration).await;
+            if in_flight.is_empty() && backing_off.is_empty() {
+                return Ok(());
             }
-        }
 
-        if !remaining_failed_requests.is_empty() {
-            eprintln!("Failed requests after retries: {:?}", remaining_failed_requests);
-            return Err(PolarsError::ComputeError("Failed to process all queries".into()));
+            let fetched = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => {
+                    let mut unfinished: Vec<AggQuery> = unfinished.into_iter().collect();
+                    unfinished.sort_by_key(|query| query.from);
+                    for query in unfinished {
+                        if sender.send(Ok(DayOutcome::cancelled(&query))).await.is_err() {
+                            break;
+                        }
+                    }
+                    return Ok(());
+                }
+                Some(fetched) = in_flight.next() => fetched,
+                Some(attempt) = backing_off.next() => {
+                    queued.push_front(attempt);
+                    continue;
+                }
+            };
+
+            let outcome = match fetched.pages {
+                Ok(pages) => {
+                    unfinished.remove(&fetched.query);
+                    DayOutcome::fetched(ticker, &fetched.query, &pages, &agg_data_schema, fetched.retries, fetched.latency)?
+                }
+                Err(error) if !error.is_retryable() => {
+                    return Err(error.for_request(ticker, &fetched.query.date_label()));
+                }
+                Err(error) => {
+                    let retries = fetched.retries;
+                    if retry_policy.should_retry(&error) && retries + 1 < retry_policy.max_attempts {
+                        // Back off exponentially, and at least as long as Polygon asked via Retry-After
+                        let backoff = retry_policy
+                            .jittered_backoff(retries + 1)
+                            .max(error.retry_after().unwrap_or_default());
+                        let attempt = Attempt { query: fetched.query, retries: retries + 1 };
+                        backing_off.push(async move {
+                            sleep(backoff).await;
+                            attempt
+                        });
+                        continue;
+                    }
+                    unfinished.remove(&fetched.query);
+                    let failed = FailedRequest { query: fetched.query, error, latency: fetched.latency };
+                    DayOutcome::exhausted(ticker, failed, retries)
+                }
+            };
+            if sender.send(Ok(outcome)).await.is_err() {
+                return Ok(());
+            }
         }
+    }
+}
 
-        Ok(())
+impl DayOutcome {
+    /// Builds the outcome of a date whose pages were all fetched.
+    fn fetched(
+        ticker: &str,
+        query: &AggQuery,
+        pages: &[Value],
+        agg_data_schema: &Arc<Schema>,
+        retries: u32,
+        latency: Duration,
+    ) -> Result<Self> {
+        let date = query.date_label();
+        let df = ResponseProcessor::process_single(&date, pages, agg_data_schema)
+            .map_err(|error| error.for_request(ticker, &date))?;
+        let df = match df {
+            Some(mut df) => {
+                DataFrameBuilder::add_ticker_column(&mut df, ticker);
+                Some(df)
+            }
+            None => None,
+        };
+
+        let bars = df.as_ref().map(|df| df.height()).unwrap_or(0);
+        let report = DateReport {
+            date,
+            status: if bars > 0 { DateStatus::Succeeded } else { DateStatus::Empty },
+            bars,
+            pages: pages.len(),
+            retries,
+            latency,
+            error: None,
+        };
+        Ok(DayOutcome { report, result: Ok(df) })
+    }
+
+    /// Builds the outcome of a date that was still failing after its last retry.
+    fn exhausted(ticker: &str, failed: FailedRequest, retries: u32) -> Self {
+        let date = failed.query.date_label();
+        let last_error = failed.error.for_request(ticker, &date);
+        let report = DateReport {
+            date: date.clone(),
+            status: DateStatus::Failed,
+            bars: 0,
+            pages: 0,
+            retries,
+            latency: failed.latency,
+            error: Some(last_error.to_string()),
+        };
+        let error = Error::RetriesExhausted {
+            context: ErrorContext::new().ticker(ticker).date(date).url(failed.query.to_url()),
+            attempts: retries + 1,
+            last_error: Box::new(last_error),
+        };
+        DayOutcome { report, result: Err(error) }
+    }
+
+    /// Builds the outcome of a date that had not finished when the extraction was cancelled.
+    fn cancelled(query: &AggQuery) -> Self {
+        let report = DateReport {
+            date: query.date_label(),
+            status: DateStatus::Cancelled,
+            bars: 0,
+            pages: 0,
+            retries: 0,
+            latency: Duration::ZERO,
+            error: None,
+        };
+        DayOutcome { report, result: Ok(None) }
     }
 }
 
-/// Extracts the date from a query string.
-fn extract_date(query: &str) -> String {
-    query
-        .split('/')
-        .nth(10)
-        .and_then(|segment| segment.split('?').next())
-        .map(|date| date.to_string())
-        .unwrap_or_else(|| query.to_string())
+/// Returns the error description from a Polygon error payload, falling back to the raw body.
+fn polygon_error_message(json: &Value, body: &str) -> String {
+    json["error"]
+        .as_str()
+        .or_else(|| json["message"].as_str())
+        .unwrap_or(body)
+        .to_string()
 }
 
 /// Processes the responses from the Polygon API.
 struct ResponseProcessor;
 
 impl ResponseProcessor {
-    /// Processes all responses and returns a vector of DataFrames.
-    async fn process_all(
-        responses: Vec<(String, reqwest::Response)>,
+    /// Processes every page fetched for a single date and returns the merged DataFrame, if any.
+    fn process_single(
+        date: &str,
+        pages: &[Value],
         agg_data_schema: &Arc<Schema>,
-    ) -> Result<Vec<DataFrame>, PolarsError> {
-        let mut df_vec = Vec::new();
-
-        for (date, response) in responses {
-            if let Some(df) = ResponseProcessor::process_single((date, Ok(response)), agg_data_schema).await? {
-                df_vec.push(df);
+    ) -> Result<Option<DataFrame>> {
+        let schema_error = |source| Error::Schema { context: ErrorContext::new().date(date), source };
+        let mut page_dfs = Vec::new();
+
+        let timezone = MarketTimezone::Eastern.timezone();
+        for json in pages {
+            if let Some(df) = DataFrameBuilder::from_json(json, timezone, agg_data_schema).map_err(schema_error)? {
+                page_dfs.push(df);
             }
         }
 
-        Ok(df_vec)
-    }
-
-    /// Processes a single response and returns an optional DataFrame.
-    async fn process_single(
-        result: (String, reqwest::Result<reqwest::Response>),
-        agg_data_schema: &Arc<Schema>,
-    ) -> Result<Option<DataFrame>, PolarsError> {
-        let (date, response) = result;
-        match response {
-            Ok(res) => {
-                let body = res.text().await.unwrap();
-                if let Ok(json) = serde_json::from_str::<Value>(&body) {
-                    DataFrameBuilder::from_json(&json, &date, agg_data_schema)
-                } else {
-                    eprintln!("Error parsing JSON: {}", body);
-                    Ok(None)
-                }
-            }
-            Err(error) => {
-                eprintln!("Error: {}", error);
-                Ok(None)
-            }
+        if page_dfs.is_empty() {
+            return Ok(None);
         }
+
+        let mut merged_df = DataFrameBuilder::create_empty();
+        DataFrameBuilder::combine(&mut merged_df, page_dfs).map_err(schema_error)?;
+
+        Ok(Some(merged_df))
     }
 }
 
@@ -262,7 +665,7 @@ impl DataFrameBuilder {
     }
 
     /// Creates a DataFrame from JSON data.
-    fn from_json(json: &Value, date: &str, agg_data_schema: &Arc<Schema>) -> Result<Option<DataFrame>, PolarsError> {
+    fn from_json(json: &Value, timezone: Tz, agg_data_schema: &Arc<Schema>) -> PolarsResult<Option<DataFrame>> {
         if let Some(results_count) = json["resultsCount"].as_u64() {
             if results_count > 0 {
                 if let Some(results) = json["results"].as_array() {
@@ -270,7 +673,7 @@ impl DataFrameBuilder {
                         let mut df = JsonReader::new(Cursor::new(json_string))
                             .with_schema(SchemaRef::from((*agg_data_schema).clone()))
                             .finish()?;
-                        DataFrameBuilder::add_date_column(&mut df, date);
+                        DataFrameBuilder::add_date_column(&mut df, timezone)?;
                         DataFrameBuilder::rename_columns(&mut df); 
                         return Ok(Some(df));
                     }
@@ -283,17 +686,27 @@ impl DataFrameBuilder {
     /// Renames the columns of the DataFrame based on the provided mapping.
     fn rename_columns(df: &mut DataFrame) {
         for (old_name, new_name) in Self::MAPPING {
-            if let Ok(_) = df.rename(old_name, new_name) {
+            if df.rename(old_name, new_name).is_ok() {
                 // Column renamed successfully
             }
         }
     }
-    /// Adds a date column to the DataFrame.
-    fn add_date_column(df: &mut DataFrame, date: &str) {
+    /// Adds the market date of each bar, taken from its timestamp in the market timezone.
+    fn add_date_column(df: &mut DataFrame, timezone: Tz) -> PolarsResult<()> {
         if !df.is_empty() {
-            let date_column = Series::new("mkt_date", vec![date.to_string(); df.height()]);
-            df.with_column(date_column).unwrap();
+            let dates: StringChunked = df
+                .column("t")?
+                .i64()?
+                .into_iter()
+                .map(|timestamp| {
+                    timestamp
+                        .and_then(DateTime::from_timestamp_millis)
+                        .map(|time| time.with_timezone(&timezone).format("%Y-%m-%d").to_string())
+                })
+                .collect();
+            df.with_column(dates.into_series().with_name("mkt_date"))?;
         }
+        Ok(())
     }
 
     /// Adds a ticker column to the DataFrame.
@@ -305,7 +718,7 @@ impl DataFrameBuilder {
     }
 
     /// Combines a vector of DataFrames into the combined DataFrame.
-    fn combine(combined_df: &mut DataFrame, df_vec: Vec<DataFrame>) -> Result<(), PolarsError> {
+    fn combine(combined_df: &mut DataFrame, df_vec: Vec<DataFrame>) -> PolarsResult<()> {
         if let Some(df) = df_vec.into_iter().reduce(|mut acc, df| {
             acc.vstack_mut(&df).unwrap();
             acc
@@ -315,10 +728,12 @@ impl DataFrameBuilder {
         Ok(())
     }
 
-    /// Finalizes the combined DataFrame by adding a ticker column and sorting by date.
-    fn finalize(combined_df: &mut DataFrame, ticker: &str) -> Result<(), PolarsError> {
-        DataFrameBuilder::add_ticker_column(combined_df, ticker);
-        combined_df.sort_in_place(&["mkt_date"], SortMultipleOptions::default())?;
+    /// Finalizes the combined DataFrame by sorting by date.
+    fn finalize(combined_df: &mut DataFrame) -> PolarsResult<()> {
+        if combined_df.height() == 0 {
+            return Ok(());
+        }
+        combined_df.sort_in_place(["mkt_date"], SortMultipleOptions::default())?;
         Ok(())
     }
 }
\ No newline at end of file
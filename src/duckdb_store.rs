@@ -0,0 +1,31 @@
+// src/duckdb_store.rs
+
+use crate::sql_store::impl_sql_store;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS bars (
+        ticker VARCHAR NOT NULL,
+        multiplier INTEGER NOT NULL,
+        timespan VARCHAR NOT NULL,
+        time BIGINT NOT NULL,
+        mkt_date VARCHAR NOT NULL,
+        open DOUBLE,
+        high DOUBLE,
+        low DOUBLE,
+        close DOUBLE,
+        volume BIGINT,
+        vwap DOUBLE,
+        transactions BIGINT,
+        PRIMARY KEY (ticker, multiplier, timespan, time)
+    );";
+
+impl_sql_store!(
+    /// Keeps processed bars in a DuckDB database, in a `bars` table keyed on
+    /// `(ticker, multiplier, timespan, time)`.
+    ///
+    /// A store reads and writes the bars of one resolution, so several stores can share a database.
+    /// Writing a bar that is already stored replaces it, so reprocessed days overwrite their old rows.
+    DuckDbStore,
+    duckdb,
+    SCHEMA
+);
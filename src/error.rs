@@ -0,0 +1,219 @@
+// src/error.rs
+
+use crate::retry::RetryClass;
+use polars::prelude::PolarsError;
+use std::fmt;
+use std::time::Duration;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Identifies the request an error relates to. Fields that do not apply are left empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    pub ticker: Option<String>,
+    pub date: Option<String>,
+    pub url: Option<String>,
+}
+
+impl ErrorContext {
+    pub fn new() -> Self {
+        ErrorContext::default()
+    }
+
+    pub fn ticker(mut self, ticker: impl Into<String>) -> Self {
+        self.ticker = Some(ticker.into());
+        self
+    }
+
+    pub fn date(mut self, date: impl Into<String>) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [("ticker", &self.ticker), ("date", &self.date), ("url", &self.url)]
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|value| format!("{}={}", name, value)))
+            .collect();
+        write!(f, "[{}]", parts.join(" "))
+    }
+}
+
+/// Errors returned by polyextract.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or its response could not be read.
+    Transport { context: ErrorContext, message: String },
+    /// Polygon answered with a non-success HTTP status.
+    HttpStatus { context: ErrorContext, status: u16, body: String, retry_after: Option<Duration> },
+    /// Polygon rejected the API key, or the key's plan does not cover the request.
+    Unauthorized { context: ErrorContext, status: u16, message: String },
+    /// Polygon answered with an error payload.
+    Api { context: ErrorContext, status: String, message: String },
+    /// The response body was not the JSON we expected.
+    Decode { context: ErrorContext, message: String },
+    /// The response data did not fit the aggregate schema, or a DataFrame operation failed.
+    Schema { context: ErrorContext, source: PolarsError },
+    /// A date could not be mapped onto the market calendar.
+    Calendar { context: ErrorContext, message: String },
+    /// The response cache could not be read or written, or an offline run asked for a response
+    /// it does not hold.
+    Cache { context: ErrorContext, message: String },
+    /// A checkpoint manifest could not be read or written.
+    Checkpoint { context: ErrorContext, message: String },
+    /// A cassette could not be read or written, or a replayed request was never recorded.
+    Cassette { context: ErrorContext, message: String },
+    /// A bar store could not be read or written.
+    Store { context: ErrorContext, message: String },
+    /// A request did not complete within the extraction's request timeout.
+    Timeout { context: ErrorContext, timeout: Duration },
+    /// The extraction did not complete within its deadline.
+    DeadlineExceeded { context: ErrorContext, deadline: Duration },
+    /// The request kept failing until the retry budget was spent.
+    RetriesExhausted { context: ErrorContext, attempts: u32, last_error: Box<Error> },
+    /// The request parameters are not supported.
+    InvalidInput { context: ErrorContext, message: String },
+    /// The session configuration is missing or invalid.
+    Config { message: String },
+}
+
+impl Error {
+    /// Returns the ticker, date and URL the error relates to.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Transport { context, .. }
+            | Error::HttpStatus { context, .. }
+            | Error::Unauthorized { context, .. }
+            | Error::Api { context, .. }
+            | Error::Decode { context, .. }
+            | Error::Schema { context, .. }
+            | Error::Calendar { context, .. }
+            | Error::Cache { context, .. }
+            | Error::Checkpoint { context, .. }
+            | Error::Cassette { context, .. }
+            | Error::Store { context, .. }
+            | Error::Timeout { context, .. }
+            | Error::DeadlineExceeded { context, .. }
+            | Error::RetriesExhausted { context, .. }
+            | Error::InvalidInput { context, .. } => Some(context),
+            Error::Config { .. } => None,
+        }
+    }
+
+    fn context_mut(&mut self) -> Option<&mut ErrorContext> {
+        match self {
+            Error::Transport { context, .. }
+            | Error::HttpStatus { context, .. }
+            | Error::Unauthorized { context, .. }
+            | Error::Api { context, .. }
+            | Error::Decode { context, .. }
+            | Error::Schema { context, .. }
+            | Error::Calendar { context, .. }
+            | Error::Cache { context, .. }
+            | Error::Checkpoint { context, .. }
+            | Error::Cassette { context, .. }
+            | Error::Store { context, .. }
+            | Error::Timeout { context, .. }
+            | Error::DeadlineExceeded { context, .. }
+            | Error::RetriesExhausted { context, .. }
+            | Error::InvalidInput { context, .. } => Some(context),
+            Error::Config { .. } => None,
+        }
+    }
+
+    /// Returns true for failures that may succeed if the request is sent again: transport
+    /// errors, timeouts, rate limiting, server errors, Polygon error payloads and truncated bodies.
+    pub fn is_retryable(&self) -> bool {
+        self.retry_class().is_some()
+    }
+
+    /// Returns the kind of failure for errors that may succeed if the request is sent again.
+    pub fn retry_class(&self) -> Option<RetryClass> {
+        match self {
+            Error::Transport { .. } => Some(RetryClass::Transport),
+            Error::Timeout { .. } => Some(RetryClass::Timeout),
+            Error::Api { .. } => Some(RetryClass::Api),
+            Error::Decode { .. } => Some(RetryClass::Decode),
+            Error::HttpStatus { status: 429, .. } => Some(RetryClass::RateLimited),
+            Error::HttpStatus { status, .. } if *status >= 500 => Some(RetryClass::ServerError),
+            _ => None,
+        }
+    }
+
+    /// Returns how long Polygon asked us to wait before retrying, if it said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Fills in the ticker and date of the request, keeping any values already set.
+    pub(crate) fn for_request(mut self, ticker: &str, date: &str) -> Self {
+        if let Some(context) = self.context_mut() {
+            context.ticker.get_or_insert_with(|| ticker.to_string());
+            context.date.get_or_insert_with(|| date.to_string());
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport { context, message } => write!(f, "transport error {}: {}", context, message),
+            Error::HttpStatus { context, status, body, .. } => {
+                write!(f, "HTTP status {} {}: {}", status, context, body)
+            }
+            Error::Unauthorized { context, status, message } => write!(
+                f,
+                "not authorized (HTTP {}) {}: {}; check the API key and that its plan covers this data",
+                status, context, message
+            ),
+            Error::Api { context, status, message } => {
+                write!(f, "Polygon API error {} {}: {}", status, context, message)
+            }
+            Error::Decode { context, message } => write!(f, "decode error {}: {}", context, message),
+            Error::Schema { context, source } => write!(f, "schema error {}: {}", context, source),
+            Error::Calendar { context, message } => write!(f, "calendar error {}: {}", context, message),
+            Error::Cache { context, message } => write!(f, "cache error {}: {}", context, message),
+            Error::Checkpoint { context, message } => write!(f, "checkpoint error {}: {}", context, message),
+            Error::Cassette { context, message } => write!(f, "cassette error {}: {}", context, message),
+            Error::Store { context, message } => write!(f, "store error {}: {}", context, message),
+            Error::Timeout { context, timeout } => {
+                write!(f, "request timed out after {:?} {}", timeout, context)
+            }
+            Error::DeadlineExceeded { context, deadline } => {
+                write!(f, "extraction deadline of {:?} exceeded {}", deadline, context)
+            }
+            Error::RetriesExhausted { context, attempts, last_error } => {
+                write!(f, "retries exhausted after {} attempts {}: {}", attempts, context, last_error)
+            }
+            Error::InvalidInput { context, message } => write!(f, "invalid input {}: {}", context, message),
+            Error::Config { message } => write!(f, "configuration error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Schema { source, .. } => Some(source),
+            Error::RetriesExhausted { last_error, .. } => Some(last_error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<PolarsError> for Error {
+    fn from(source: PolarsError) -> Self {
+        Error::Schema { context: ErrorContext::default(), source }
+    }
+}
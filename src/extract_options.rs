@@ -0,0 +1,53 @@
+// src/extract_options.rs
+
+use crate::retry::RetryPolicy;
+use std::time::Duration;
+
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 100;
+
+/// Concurrency, timeout and retry settings of an extraction.
+#[derive(Clone, Debug)]
+pub struct ExtractOptions {
+    /// How many requests may be in flight at once.
+    pub max_in_flight: usize,
+    /// How long a single page request may take, connecting included, before it fails with
+    /// `Error::Timeout`. The session's transport applies its own connect and request timeouts
+    /// on top of this.
+    pub request_timeout: Option<Duration>,
+    /// How long the whole extraction may take before it fails with `Error::DeadlineExceeded`.
+    pub deadline: Option<Duration>,
+    pub retry_policy: RetryPolicy,
+}
+
+impl ExtractOptions {
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            request_timeout: None,
+            deadline: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+}
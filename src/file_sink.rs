@@ -0,0 +1,151 @@
+// src/file_sink.rs
+//
+// Layout and timestamp handling shared by the CSV, Arrow IPC and NDJSON sinks.
+
+use crate::atomic_file::write_atomically;
+use crate::error::{Error, ErrorContext, Result};
+use crate::sink::{partition_by_month, ticker_dir};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDate, SecondsFormat};
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::fs::File;
+use std::path::PathBuf;
+
+/// How the `time` column is written.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Milliseconds since the Unix epoch, as the extractor returns them.
+    #[default]
+    EpochMillis,
+    /// A native datetime column in the sink's timezone, for formats that have one.
+    Datetime,
+    /// RFC 3339 strings with millisecond precision and the UTC offset, e.g.
+    /// `2024-01-02T09:30:00.000-05:00`.
+    Rfc3339,
+    /// Strings formatted with the given `chrono` format, e.g. `%Y-%m-%d %H:%M:%S`.
+    Strftime(String),
+}
+
+impl TimestampFormat {
+    /// Checks that a `Strftime` format only holds specifiers `chrono` understands.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            TimestampFormat::Strftime(format) => strftime_items(format).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the bars with their `time` column converted to this format in the given timezone.
+    ///
+    /// Fails with `Error::InvalidInput` if a `Strftime` format is invalid.
+    pub fn apply(&self, bars: &DataFrame, timezone: Tz) -> Result<DataFrame> {
+        let render: Box<dyn Fn(DateTime<Tz>) -> String> = match self {
+            TimestampFormat::EpochMillis => return Ok(bars.clone()),
+            TimestampFormat::Datetime => {
+                let mut converted = bars.clone();
+                let time = bars
+                    .column("time")?
+                    .cast(&DataType::Datetime(TimeUnit::Milliseconds, Some(timezone.name().to_string())))?;
+                converted.with_column(time)?;
+                return Ok(converted);
+            }
+            TimestampFormat::Rfc3339 => Box::new(|time| time.to_rfc3339_opts(SecondsFormat::Millis, false)),
+            TimestampFormat::Strftime(format) => {
+                let items = strftime_items(format)?;
+                Box::new(move |time| time.format_with_items(items.iter()).to_string())
+            }
+        };
+
+        let times: Vec<Option<String>> = bars
+            .column("time")?
+            .i64()?
+            .into_iter()
+            .map(|millis| {
+                millis
+                    .and_then(DateTime::from_timestamp_millis)
+                    .map(|time| render(time.with_timezone(&timezone)))
+            })
+            .collect();
+        let mut converted = bars.clone();
+        converted.with_column(Series::new("time", times))?;
+        Ok(converted)
+    }
+}
+
+/// Parses a `strftime` format, rejecting unknown specifiers, which `chrono` would otherwise
+/// only report by panicking while formatting.
+fn strftime_items(format: &str) -> Result<Vec<Item<'_>>> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.contains(&Item::Error) {
+        return Err(Error::InvalidInput {
+            context: ErrorContext::new(),
+            message: format!("Invalid strftime format {:?}", format),
+        });
+    }
+    Ok(items)
+}
+
+/// Where a file sink writes and how it formats timestamps.
+///
+/// Bars are written to one file per ticker and `mkt_date`,
+/// `ticker=AAPL/year=2024/month=01/2024-01-02.{extension}`, so rewriting a day replaces its file
+/// rather than appending to it. Files are replaced atomically.
+#[derive(Clone, Debug)]
+pub(crate) struct DailyFiles {
+    pub dir: PathBuf,
+    pub extension: &'static str,
+    pub timestamps: TimestampFormat,
+    pub timezone: Tz,
+}
+
+impl DailyFiles {
+    /// Writes epoch millisecond timestamps in UTC below the given directory.
+    pub fn new(dir: PathBuf, extension: &'static str) -> Self {
+        DailyFiles { dir, extension, timestamps: TimestampFormat::default(), timezone: Tz::UTC }
+    }
+
+    /// Returns the file holding the ticker's bars of the given day.
+    pub fn path(&self, ticker: &str, mkt_date: NaiveDate) -> PathBuf {
+        ticker_dir(&self.dir, ticker)
+            .join(mkt_date.format("year=%Y/month=%m").to_string())
+            .join(format!("{}.{}", mkt_date.format("%Y-%m-%d"), self.extension))
+    }
+
+    /// Converts the timestamps and writes each day with `write`, on a blocking thread.
+    pub async fn write(
+        &self,
+        bars: &DataFrame,
+        write: impl Fn(&mut DataFrame, File) -> PolarsResult<()> + Send + 'static,
+    ) -> Result<()> {
+        self.timestamps.validate()?;
+        let files = self.clone();
+        let bars = bars.clone();
+        tokio::task::spawn_blocking(move || {
+            let bars = files.timestamps.apply(&bars, files.timezone)?;
+            files.write_days(&bars, write)
+        })
+        .await
+        .map_err(|e| Error::Store { context: ErrorContext::new(), message: e.to_string() })?
+    }
+
+    fn write_days(&self, bars: &DataFrame, write: impl Fn(&mut DataFrame, File) -> PolarsResult<()>) -> Result<()> {
+        for partition in partition_by_month(bars)? {
+            for mut day in partition.bars.partition_by_stable(["mkt_date"], true)? {
+                let mkt_date = day.column("mkt_date")?.str()?.get(0).unwrap_or_default().to_string();
+                let dir = partition.dir(&self.dir);
+                let path = dir.join(format!("{}.{}", mkt_date, self.extension));
+                let error = |message: String| Error::Store {
+                    context: ErrorContext::new().ticker(&partition.ticker).date(&mkt_date).url(path.display().to_string()),
+                    message,
+                };
+
+                std::fs::create_dir_all(&dir).map_err(|e| error(e.to_string()))?;
+
+                write_atomically(&path, |file| write(&mut day, file).map_err(std::io::Error::other))
+                    .map_err(|e| error(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+}
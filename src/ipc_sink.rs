@@ -0,0 +1,63 @@
+// src/ipc_sink.rs
+
+use crate::error::Result;
+use crate::file_sink::{DailyFiles, TimestampFormat};
+use crate::sink::Sink;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Writes processed bars to Arrow IPC (Feather v2) files, one per ticker and day, laid out like
+/// `ParquetSink`'s partitions: `ticker=AAPL/year=2024/month=01/2024-01-02.arrow`.
+///
+/// Files are uncompressed by default, so readers can memory map them without copying.
+#[derive(Clone, Debug)]
+pub struct IpcSink {
+    files: DailyFiles,
+    compression: Option<IpcCompression>,
+}
+
+impl IpcSink {
+    /// Creates a sink writing uncompressed files below the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        IpcSink { files: DailyFiles::new(dir.into(), "arrow"), compression: None }
+    }
+
+    /// Sets how the `time` column is written. An invalid `Strftime` format fails every write.
+    pub fn timestamps(mut self, timestamps: TimestampFormat) -> Self {
+        self.files.timestamps = timestamps;
+        self
+    }
+
+    /// Sets the timezone formatted timestamps are written in. Defaults to UTC.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.files.timezone = timezone;
+        self
+    }
+
+    pub fn compression(mut self, compression: IpcCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.files.dir
+    }
+
+    /// Returns the file holding the ticker's bars of the given day.
+    pub fn path(&self, ticker: &str, mkt_date: NaiveDate) -> PathBuf {
+        self.files.path(ticker, mkt_date)
+    }
+}
+
+#[async_trait]
+impl Sink for IpcSink {
+    async fn write(&self, bars: &DataFrame) -> Result<()> {
+        let compression = self.compression;
+        self.files
+            .write(bars, move |day, file| IpcWriter::new(file).with_compression(compression).finish(day))
+            .await
+    }
+}
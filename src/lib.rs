@@ -1,22 +1,90 @@
 // src/lib.rs
 
+pub mod agg_query;
+mod atomic_file;
+pub mod cache;
+pub mod calendar;
+pub mod cassette;
+pub mod checkpoint;
 pub mod config;
+#[cfg(feature = "csv")]
+pub mod csv_sink;
+pub mod error;
+pub mod extract_options;
+#[cfg(any(feature = "csv", feature = "ipc", feature = "ndjson"))]
+pub mod file_sink;
 pub mod session;
+pub mod transport;
 pub mod data_extractor;
+#[cfg(feature = "store-duckdb")]
+pub mod duckdb_store;
+#[cfg(feature = "ipc")]
+pub mod ipc_sink;
+pub mod adaptive_rate;
+pub mod agg_extractor;
 pub mod minute_extractor;
+#[cfg(feature = "mock-server")]
+pub mod mock_server;
+#[cfg(feature = "ndjson")]
+pub mod ndjson_sink;
 pub mod poly_agg_info;
 
+pub mod parquet_sink;
 pub mod processor;
+pub mod rate_limiter;
+pub mod report;
+pub mod retry;
+pub mod sink;
+#[cfg(any(feature = "store-sqlite", feature = "store-duckdb"))]
+mod sql_store;
+#[cfg(feature = "store-sqlite")]
+pub mod sqlite_store;
+pub mod store;
 mod ticker_manager;
 mod ticker_manager_pool;
+pub mod update;
 
+pub use adaptive_rate::{AdaptiveRate, AdaptiveRateController, RateChange, RateChangeReason};
+pub use agg_query::{AggQuery, SortOrder};
+pub use cache::{CacheMode, ResponseCache};
+pub use calendar::{latest_closed_session, EveryDayCalendar, NyseCalendar, TradingCalendar};
+pub use cassette::{RecordingTransport, ReplayTransport};
+pub use checkpoint::{CheckpointKey, CheckpointStore};
+pub use config::SessionConfig;
+#[cfg(feature = "csv")]
+pub use csv_sink::CsvSink;
+pub use error::{Error, ErrorContext, Result};
+pub use extract_options::ExtractOptions;
+#[cfg(any(feature = "csv", feature = "ipc", feature = "ndjson"))]
+pub use file_sink::TimestampFormat;
+pub use parquet_sink::ParquetSink;
+pub use rate_limiter::{RateLimiter, RatePlan};
+pub use retry::{RetryClass, RetryPolicy};
 pub use session::PolygonHistorySession;
+pub use sink::Sink;
+#[cfg(feature = "store-sqlite")]
+pub use sqlite_store::SqliteStore;
+pub use store::{BarStore, MemoryBarStore, BAR_COLUMNS};
+pub use transport::{FixtureTransport, ReqwestTransport, Transport, TransportResponse};
 
-pub use data_extractor::AggDataExtractor;
+pub use data_extractor::{AggDataExtractor, DayBatch};
+#[cfg(feature = "store-duckdb")]
+pub use duckdb_store::DuckDbStore;
+#[cfg(feature = "ipc")]
+pub use ipc_sink::IpcSink;
+pub use agg_extractor::AggExtractor;
 pub use minute_extractor::MinuteExtractor;
+#[cfg(feature = "mock-server")]
+pub use mock_server::{Fault, MockPolygonServer};
+#[cfg(feature = "ndjson")]
+pub use ndjson_sink::NdjsonSink;
 pub use poly_agg_info::PolyAggInfo;
+pub use poly_agg_info::Timespan;
 pub use processor::MADOutlierDetector;
 pub use processor::Processor;
 pub use processor::MarketTimezone;
+pub use report::{DateReport, DateStatus, ExtractionReport, PoolReport};
 pub use ticker_manager::TickerManager;
-pub use ticker_manager_pool::TickerManagerPool;
\ No newline at end of file
+pub use ticker_manager_pool::TickerManagerPool;
+pub use tokio_util::sync::CancellationToken;
+pub use update::Updater;
\ No newline at end of file
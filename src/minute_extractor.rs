@@ -1,21 +1,19 @@
 // src/minute_extractor.rs
 
+use super::agg_extractor::AggExtractor;
 use super::data_extractor::AggDataExtractor;
-use super::poly_agg_info::PolyAggInfo;
+use super::poly_agg_info::{PolyAggInfo, Timespan};
+use super::session::PolygonHistorySession;
+use std::sync::Arc;
 
+/// Extracts one-minute bars; an `AggExtractor` whose resolution is fixed to minutes.
 pub struct MinuteExtractor {
     pub extractor: AggDataExtractor,
 }
 
 impl MinuteExtractor {
-    pub fn new(poly_agg_info: PolyAggInfo) -> Self {
-        let base_query = "https://api.polygon.io/v2/aggs/ticker/{ticker}/range/1/minute/{start_date}/{end_date}?adjusted=true&sort=asc&limit={limit}".to_string();
-        let limit = "5000".to_string();
-        let data_extractor = AggDataExtractor {
-            poly_agg_info,
-            base_query,
-            limit,
-        };
-        MinuteExtractor { extractor: data_extractor }
+    pub fn new(poly_agg_info: PolyAggInfo, session: Arc<PolygonHistorySession>) -> Self {
+        let poly_agg_info = PolyAggInfo { resolution: Timespan::Minute.to_string(), ..poly_agg_info };
+        MinuteExtractor { extractor: AggExtractor::new(poly_agg_info, session).extractor }
     }
-}
\ No newline at end of file
+}
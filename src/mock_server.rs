@@ -0,0 +1,441 @@
+// src/mock_server.rs
+
+use crate::calendar::{calendar_for_ticker, trades_around_the_clock};
+use crate::error::{Error, ErrorContext, Result};
+use crate::poly_agg_info::Timespan;
+use crate::processor::MarketTimezone;
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveTime, TimeZone};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// A failure the mock server can answer with instead of bars.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// HTTP 429, with an optional `Retry-After` header in seconds.
+    RateLimited { retry_after: Option<u64> },
+    /// The given 5xx status.
+    ServerError(u16),
+    /// HTTP 200 with a truncated JSON body.
+    MalformedJson,
+}
+
+/// Configures a `MockPolygonServer` before it starts.
+#[derive(Clone, Debug, Default)]
+pub struct MockPolygonServerBuilder {
+    seed: u64,
+    api_key: Option<String>,
+    page_size: Option<usize>,
+    latency: Duration,
+    first_faults: Vec<(Fault, u32)>,
+    date_faults: Vec<(NaiveDate, Fault, u32)>,
+    empty_dates: HashSet<NaiveDate>,
+}
+
+impl MockPolygonServerBuilder {
+    /// Seeds the synthetic price process; the same seed always serves the same bars.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Answers requests without this `apiKey` with 401.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Splits responses into pages of at most this many bars, linked by `next_url`.
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size.max(1));
+        self
+    }
+
+    /// Delays every response by the given duration.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Answers the first `times` requests, whatever they ask for, with the fault.
+    pub fn fail_first(mut self, fault: Fault, times: u32) -> Self {
+        self.first_faults.push((fault, times));
+        self
+    }
+
+    /// Answers the first `times` requests starting on the given date with the fault.
+    pub fn fail_date(mut self, date: NaiveDate, fault: Fault, times: u32) -> Self {
+        self.date_faults.push((date, fault, times));
+        self
+    }
+
+    /// Serves no bars for the given date, as on an unscheduled holiday.
+    pub fn empty_date(mut self, date: NaiveDate) -> Self {
+        self.empty_dates.insert(date);
+        self
+    }
+
+    /// Binds to a free port on localhost and starts serving.
+    pub async fn start(self) -> Result<MockPolygonServer> {
+        let server_error = |e: std::io::Error| Error::Transport { context: ErrorContext::new(), message: e.to_string() };
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(server_error)?;
+        let addr = listener.local_addr().map_err(server_error)?;
+
+        let state = Arc::new(MockState {
+            base_url: format!("http://{}", addr),
+            config: self,
+            faults_served: Mutex::new(HashMap::new()),
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let accept_state = Arc::clone(&state);
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&accept_state);
+                tokio::spawn(async move {
+                    let _ = state.handle_connection(stream).await;
+                });
+            }
+        });
+
+        Ok(MockPolygonServer { addr, state, handle })
+    }
+}
+
+/// A local HTTP server that imitates the Polygon aggregates endpoint.
+///
+/// Bars come from a seeded random walk, so tests can assert on exact values. Faults, empty
+/// dates, pagination and latency are configured through `MockPolygonServer::builder`. The server
+/// stops when dropped.
+pub struct MockPolygonServer {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    handle: JoinHandle<()>,
+}
+
+impl MockPolygonServer {
+    pub fn builder() -> MockPolygonServerBuilder {
+        MockPolygonServerBuilder::default()
+    }
+
+    /// Returns the URL to use as the session's base URL.
+    pub fn base_url(&self) -> &str {
+        &self.state.base_url
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the path and query of every request received so far, without the `apiKey` parameter.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockPolygonServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+struct MockState {
+    base_url: String,
+    config: MockPolygonServerBuilder,
+    /// How many times each configured fault has been served, keyed by its position.
+    faults_served: Mutex<HashMap<usize, u32>>,
+    requests: Mutex<Vec<String>>,
+}
+
+/// A parsed aggregates request.
+struct AggRequest {
+    ticker: String,
+    multiplier: u32,
+    timespan: Timespan,
+    from: NaiveDate,
+    to: NaiveDate,
+    limit: usize,
+    cursor: usize,
+}
+
+/// The status, extra headers and body of a response.
+struct MockResponse {
+    status: u16,
+    retry_after: Option<u64>,
+    body: String,
+}
+
+impl MockResponse {
+    fn json(status: u16, body: Value) -> Self {
+        MockResponse { status, retry_after: None, body: body.to_string() }
+    }
+}
+
+impl MockState {
+    async fn handle_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+
+        let request = String::from_utf8_lossy(&buffer);
+        let target = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+        let response = self.respond(&target);
+
+        if !self.config.latency.is_zero() {
+            tokio::time::sleep(self.config.latency).await;
+        }
+
+        let retry_after = response
+            .retry_after
+            .map(|seconds| format!("Retry-After: {}\r\n", seconds))
+            .unwrap_or_default();
+        let head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+            response.status,
+            reason_phrase(response.status),
+            response.body.len(),
+            retry_after
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(response.body.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    fn respond(&self, target: &str) -> MockResponse {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let params: HashMap<String, String> = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .map(|(name, value)| (name.to_string(), urlencoding::decode(value).map(|v| v.into_owned()).unwrap_or_default()))
+            .collect();
+
+        let logged_query: Vec<&str> = query.split('&').filter(|param| !param.starts_with("apiKey=")).collect();
+        self.requests.lock().unwrap().push(format!("{}?{}", path, logged_query.join("&")));
+
+        if let Some(api_key) = &self.config.api_key {
+            if params.get("apiKey") != Some(api_key) {
+                return MockResponse::json(
+                    401,
+                    json!({"status": "NOT_AUTHORIZED", "request_id": "mock", "message": "Unknown API Key"}),
+                );
+            }
+        }
+
+        let request = match parse_request(path, &params) {
+            Some(request) => request,
+            None => {
+                return MockResponse::json(
+                    404,
+                    json!({"status": "NOT_FOUND", "request_id": "mock", "message": "Unknown endpoint"}),
+                )
+            }
+        };
+
+        if let Some(fault) = self.next_fault(request.from) {
+            return match fault {
+                Fault::RateLimited { retry_after } => MockResponse {
+                    retry_after,
+                    ..MockResponse::json(429, json!({"status": "ERROR", "request_id": "mock", "error": "Too many requests"}))
+                },
+                Fault::ServerError(status) => MockResponse { status, retry_after: None, body: "Internal Server Error".to_string() },
+                Fault::MalformedJson => MockResponse { status: 200, retry_after: None, body: "{\"ticker\":\"".to_string() },
+            };
+        }
+
+        self.bars_page(&request, path, &params)
+    }
+
+    /// Returns the first configured fault that still applies to a request starting on `from`.
+    fn next_fault(&self, from: NaiveDate) -> Option<Fault> {
+        let mut served = self.faults_served.lock().unwrap();
+        let first_faults = self.config.first_faults.iter().map(|(fault, times)| (None, *fault, *times));
+        let date_faults = self.config.date_faults.iter().map(|(date, fault, times)| (Some(*date), *fault, *times));
+
+        for (index, (date, fault, times)) in first_faults.chain(date_faults).enumerate() {
+            if date.is_some_and(|date| date != from) {
+                continue;
+            }
+            let count = served.entry(index).or_insert(0);
+            if *count < times {
+                *count += 1;
+                return Some(fault);
+            }
+        }
+        None
+    }
+
+    fn bars_page(&self, request: &AggRequest, path: &str, params: &HashMap<String, String>) -> MockResponse {
+        let bars = self.generate_bars(request);
+        let page_size = self.config.page_size.unwrap_or(request.limit).min(request.limit).max(1);
+        let page: Vec<Value> = bars.iter().skip(request.cursor).take(page_size).cloned().collect();
+
+        let mut body = json!({
+            "ticker": request.ticker,
+            "queryCount": page.len(),
+            "resultsCount": page.len(),
+            "adjusted": params.get("adjusted").map(|adjusted| adjusted == "true").unwrap_or(true),
+            "status": "OK",
+            "request_id": "mock",
+            "count": page.len(),
+        });
+        if !page.is_empty() {
+            body["results"] = Value::Array(page);
+        }
+
+        let next_cursor = request.cursor + page_size;
+        if next_cursor < bars.len() {
+            let mut next_params: Vec<String> = params
+                .iter()
+                .filter(|(name, _)| name.as_str() != "apiKey" && name.as_str() != "cursor")
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            next_params.sort();
+            next_params.push(format!("cursor={}", next_cursor));
+            body["next_url"] = json!(format!("{}{}?{}", self.base_url, path, next_params.join("&")));
+        }
+
+        MockResponse::json(200, body)
+    }
+
+    /// Generates every bar of the request, in ascending time order.
+    fn generate_bars(&self, request: &AggRequest) -> Vec<Value> {
+        let calendar = calendar_for_ticker(&request.ticker);
+        let timezone = MarketTimezone::Eastern.timezone();
+        let crypto = trades_around_the_clock(&request.ticker);
+        let step = request.timespan.min_seconds() * i64::from(request.multiplier.max(1));
+
+        let mut bars = Vec::new();
+        let mut date = request.from;
+        while date <= request.to {
+            if calendar.is_trading_day(date) && !self.config.empty_dates.contains(&date) {
+                // Stocks trade from the 4:00 pre-market to the 20:00 close of after-hours
+                let (start, end) = if crypto { (0, 24) } else { (4, 20) };
+                let day_start = timezone
+                    .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(start, 0, 0).unwrap()))
+                    .single();
+                let midnight = timezone.from_local_datetime(&date.and_time(NaiveTime::MIN)).single();
+
+                if let (Some(day_start), Some(midnight)) = (day_start, midnight) {
+                    let mut walk = PriceWalk::new(self.config.seed, &request.ticker, date);
+                    match request.timespan {
+                        Timespan::Second | Timespan::Minute | Timespan::Hour => {
+                            let session_seconds = i64::from(end - start) * 3600;
+                            let mut offset = 0;
+                            while offset < session_seconds {
+                                bars.push(walk.bar((day_start.timestamp() + offset) * 1000));
+                                offset += step;
+                            }
+                        }
+                        _ => bars.push(walk.bar(midnight.timestamp() * 1000)),
+                    }
+                }
+            }
+            date += ChronoDuration::days(1);
+        }
+
+        // Coarser than daily bars keep one bar per period
+        if step > ChronoDuration::days(1).num_seconds() {
+            let mut last_period = None;
+            bars.retain(|bar| {
+                let period = bar["t"].as_i64().unwrap_or(0) / 1000 / step;
+                let keep = last_period != Some(period);
+                last_period = Some(period);
+                keep
+            });
+        }
+        bars
+    }
+}
+
+/// A random walk seeded from the server seed, ticker and date, so every page and every repeated
+/// request sees the same prices.
+struct PriceWalk {
+    state: u64,
+    price: f64,
+}
+
+impl PriceWalk {
+    fn new(seed: u64, ticker: &str, date: NaiveDate) -> Self {
+        let ticker_hash = ticker.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        let day = date.signed_duration_since(NaiveDate::default()).num_days() as u64;
+        let mut walk = PriceWalk { state: seed ^ ticker_hash ^ day.wrapping_mul(0x9e37_79b9_7f4a_7c15), price: 0.0 };
+        walk.price = 50.0 + walk.next_unit() * 150.0;
+        walk
+    }
+
+    /// Returns the next value of a splitmix64 generator, scaled to [0, 1).
+    fn next_unit(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn bar(&mut self, timestamp: i64) -> Value {
+        let open = self.price;
+        let close = (open * (1.0 + (self.next_unit() - 0.5) * 0.004)).max(0.01);
+        let high = open.max(close) * (1.0 + self.next_unit() * 0.001);
+        let low = open.min(close) * (1.0 - self.next_unit() * 0.001);
+        let volume = 100 + (self.next_unit() * 10_000.0) as i64;
+        let transactions = 1 + volume / 100;
+        self.price = close;
+
+        let round = |value: f64| (value * 10_000.0).round() / 10_000.0;
+        json!({
+            "v": volume,
+            "vw": round((open + high + low + close) / 4.0),
+            "o": round(open),
+            "c": round(close),
+            "h": round(high),
+            "l": round(low),
+            "t": timestamp,
+            "n": transactions,
+        })
+    }
+}
+
+/// Parses `/v2/aggs/ticker/{ticker}/range/{multiplier}/{timespan}/{from}/{to}`.
+fn parse_request(path: &str, params: &HashMap<String, String>) -> Option<AggRequest> {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["v2", "aggs", "ticker", ticker, "range", multiplier, timespan, from, to] => Some(AggRequest {
+            ticker: urlencoding::decode(ticker).ok()?.into_owned(),
+            multiplier: multiplier.parse().ok()?,
+            timespan: timespan.parse().ok()?,
+            from: NaiveDate::parse_from_str(from, "%Y-%m-%d").ok()?,
+            to: NaiveDate::parse_from_str(to, "%Y-%m-%d").ok()?,
+            limit: params.get("limit").and_then(|limit| limit.parse().ok()).unwrap_or(5000),
+            cursor: params.get("cursor").and_then(|cursor| cursor.parse().ok()).unwrap_or(0),
+        }),
+        _ => None,
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
@@ -0,0 +1,59 @@
+// src/ndjson_sink.rs
+
+use crate::error::Result;
+use crate::file_sink::{DailyFiles, TimestampFormat};
+use crate::sink::Sink;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Writes processed bars as newline delimited JSON, one object per bar and one file per ticker
+/// and day, laid out like `ParquetSink`'s partitions: `ticker=AAPL/year=2024/month=01/2024-01-02.ndjson`.
+///
+/// JSON has no datetime type, so `TimestampFormat::Datetime` is written as RFC 3339 strings.
+#[derive(Clone, Debug)]
+pub struct NdjsonSink {
+    files: DailyFiles,
+}
+
+impl NdjsonSink {
+    /// Creates a sink writing files below the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        NdjsonSink { files: DailyFiles::new(dir.into(), "ndjson") }
+    }
+
+    /// Sets how the `time` column is written. An invalid `Strftime` format fails every write.
+    pub fn timestamps(mut self, timestamps: TimestampFormat) -> Self {
+        self.files.timestamps = match timestamps {
+            TimestampFormat::Datetime => TimestampFormat::Rfc3339,
+            timestamps => timestamps,
+        };
+        self
+    }
+
+    /// Sets the timezone formatted timestamps are written in. Defaults to UTC.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.files.timezone = timezone;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.files.dir
+    }
+
+    /// Returns the file holding the ticker's bars of the given day.
+    pub fn path(&self, ticker: &str, mkt_date: NaiveDate) -> PathBuf {
+        self.files.path(ticker, mkt_date)
+    }
+}
+
+#[async_trait]
+impl Sink for NdjsonSink {
+    async fn write(&self, bars: &DataFrame) -> Result<()> {
+        self.files
+            .write(bars, |day, file| JsonWriter::new(file).with_json_format(JsonFormat::JsonLines).finish(day))
+            .await
+    }
+}
@@ -0,0 +1,199 @@
+// src/parquet_sink.rs
+
+use crate::atomic_file::write_atomically;
+use crate::error::{Error, ErrorContext, Result};
+use crate::sink::{partition_by_month, ticker_dir, Sink};
+use crate::store::{self, BarStore};
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+use std::fs::File;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const FILE_NAME: &str = "data.parquet";
+
+/// Writes processed bars to a hive partitioned Parquet dataset.
+///
+/// Each ticker and month of `mkt_date` has one file, `ticker=AAPL/year=2023/month=01/data.parquet`,
+/// holding a single resolution. Writing to a month that already has a file merges the new bars
+/// into it, so reprocessed days replace their old bars. Files are replaced atomically.
+#[derive(Clone, Debug)]
+pub struct ParquetSink {
+    dir: PathBuf,
+    compression: ParquetCompression,
+    row_group_size: Option<usize>,
+    statistics: bool,
+    /// Serializes read-modify-write cycles on the files within this process.
+    lock: Arc<Mutex<()>>,
+}
+
+impl ParquetSink {
+    /// Creates a sink writing zstd compressed files below the given directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ParquetSink {
+            dir: dir.into(),
+            compression: ParquetCompression::Zstd(None),
+            row_group_size: None,
+            statistics: true,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn compression(mut self, compression: ParquetCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Limits the number of rows per row group. By default each file is a single row group.
+    pub fn row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = Some(row_group_size.max(1));
+        self
+    }
+
+    /// Writes min/max statistics for every column, which lets readers skip row groups.
+    pub fn statistics(mut self, statistics: bool) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the file holding the ticker's bars of the given month.
+    pub fn path(&self, ticker: &str, year: i32, month: u32) -> PathBuf {
+        ticker_dir(&self.dir, ticker)
+            .join(format!("year={}", year))
+            .join(format!("month={:02}", month))
+            .join(FILE_NAME)
+    }
+
+    /// Returns the file of the ticker's latest month, if it has any.
+    fn last_path(&self, ticker: &str) -> Result<Option<PathBuf>> {
+        let year_dir = match partition_dirs(&ticker_dir(&self.dir, ticker), "year", ticker)?.pop() {
+            Some((_, year_dir)) => year_dir,
+            None => return Ok(None),
+        };
+        Ok(partition_dirs(&year_dir, "month", ticker)?.pop().map(|(_, month_dir)| month_dir.join(FILE_NAME)))
+    }
+
+    fn write_partitions(&self, bars: &DataFrame) -> Result<()> {
+        for partition in partition_by_month(bars)? {
+            let path = partition.dir(&self.dir).join(FILE_NAME);
+            let error = |message: String| Error::Store {
+                context: ErrorContext::new().ticker(&partition.ticker).url(path.display().to_string()),
+                message,
+            };
+
+            let stored = read_file(&path, &partition.ticker)?.unwrap_or_default();
+            let mut merged = store::merge(&stored, &partition.bars)?;
+
+            std::fs::create_dir_all(partition.dir(&self.dir)).map_err(|e| error(e.to_string()))?;
+
+            write_atomically(&path, |file| {
+                ParquetWriter::new(file)
+                    .with_compression(self.compression)
+                    .with_row_group_size(self.row_group_size)
+                    .with_statistics(self.statistics)
+                    .finish(&mut merged)
+                    .map(|_| ())
+                    .map_err(std::io::Error::other)
+            })
+            .map_err(|e| error(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Sink for ParquetSink {
+    async fn write(&self, bars: &DataFrame) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let sink = self.clone();
+        let bars = bars.clone();
+        tokio::task::spawn_blocking(move || sink.write_partitions(&bars))
+            .await
+            .map_err(|e| Error::Store { context: ErrorContext::new(), message: e.to_string() })?
+    }
+}
+
+#[async_trait]
+impl BarStore for ParquetSink {
+    async fn last_date(&self, ticker: &str) -> Result<Option<NaiveDate>> {
+        let path = match self.last_path(ticker)? {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        match read_file(&path, ticker)? {
+            Some(bars) => store::last_date(&bars, ticker),
+            None => Ok(None),
+        }
+    }
+
+    async fn append(&self, bars: &DataFrame) -> Result<()> {
+        self.write(bars).await
+    }
+
+    async fn read(&self, ticker: &str, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        // Only the months the ticker has files for are read, however wide the range
+        let first = (range.start().year(), range.start().month() as i32);
+        let last = (range.end().year(), range.end().month() as i32);
+        let mut bars = DataFrame::default();
+        for (year, year_dir) in partition_dirs(&ticker_dir(&self.dir, ticker), "year", ticker)? {
+            if year < first.0 || year > last.0 {
+                continue;
+            }
+            for (month, month_dir) in partition_dirs(&year_dir, "month", ticker)? {
+                if (year, month) < first || (year, month) > last {
+                    continue;
+                }
+                if let Some(stored) = read_file(&month_dir.join(FILE_NAME), ticker)? {
+                    bars = store::merge(&bars, &stored)?;
+                }
+            }
+        }
+        store::select_range(&bars, ticker, range)
+    }
+}
+
+/// Reads a partition file; none if it does not exist.
+fn read_file(path: &Path, ticker: &str) -> Result<Option<DataFrame>> {
+    let error = |message: String| Error::Store {
+        context: ErrorContext::new().ticker(ticker).url(path.display().to_string()),
+        message,
+    };
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(error(e.to_string())),
+    };
+    ParquetReader::new(file).finish().map(Some).map_err(|e| error(e.to_string()))
+}
+
+/// Returns the `key=value` subdirectories of the directory with a numeric value, in value order.
+fn partition_dirs(dir: &Path, key: &str, ticker: &str) -> Result<Vec<(i32, PathBuf)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(Error::Store {
+                context: ErrorContext::new().ticker(ticker).url(dir.display().to_string()),
+                message: e.to_string(),
+            })
+        }
+    };
+    let prefix = format!("{}=", key);
+    let mut dirs: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let value = entry.file_name().to_string_lossy().strip_prefix(&prefix)?.parse().ok()?;
+            Some((value, entry.path()))
+        })
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
@@ -1,6 +1,9 @@
 // src/extractors/poly_agg_info.rs
 
+use crate::error::{Error, ErrorContext, Result};
 use chrono::NaiveDate;
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Clone)]
 pub struct PolyAggInfo {
@@ -30,4 +33,90 @@ impl PolyAggInfo {
             })
             .collect()
     }
-}
\ No newline at end of file
+
+    /// Parses the resolution string into a Polygon aggregate timespan.
+    pub fn timespan(&self) -> Result<Timespan> {
+        self.resolution.parse::<Timespan>().map_err(|_| Error::InvalidInput {
+            context: ErrorContext::new().ticker(&self.ticker),
+            message: format!("Unsupported resolution: {}", self.resolution),
+        })
+    }
+}
+
+/// The bar sizes supported by the Polygon aggregates endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Timespan {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Timespan {
+    /// Returns the path segment used by Polygon for this timespan.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Timespan::Second => "second",
+            Timespan::Minute => "minute",
+            Timespan::Hour => "hour",
+            Timespan::Day => "day",
+            Timespan::Week => "week",
+            Timespan::Month => "month",
+            Timespan::Quarter => "quarter",
+            Timespan::Year => "year",
+        }
+    }
+
+    /// Returns the shortest length of one bar of this timespan, in seconds.
+    pub fn min_seconds(&self) -> i64 {
+        const DAY: i64 = 24 * 60 * 60;
+        match self {
+            Timespan::Second => 1,
+            Timespan::Minute => 60,
+            Timespan::Hour => 60 * 60,
+            Timespan::Day => DAY,
+            Timespan::Week => 7 * DAY,
+            Timespan::Month => 28 * DAY,
+            Timespan::Quarter => 89 * DAY,
+            Timespan::Year => 365 * DAY,
+        }
+    }
+
+    /// Returns whether bars of this timespan fit within the regular session, so the market hours
+    /// filter and outlier pass apply to them. Hour bars straddle the open and coarser bars are
+    /// stamped at midnight.
+    pub fn is_intraday(&self) -> bool {
+        matches!(self, Timespan::Second | Timespan::Minute)
+    }
+}
+
+impl FromStr for Timespan {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "second" => Ok(Timespan::Second),
+            "minute" => Ok(Timespan::Minute),
+            "hour" => Ok(Timespan::Hour),
+            "day" => Ok(Timespan::Day),
+            "week" => Ok(Timespan::Week),
+            "month" => Ok(Timespan::Month),
+            "quarter" => Ok(Timespan::Quarter),
+            "year" => Ok(Timespan::Year),
+            _ => Err(Error::InvalidInput {
+                context: ErrorContext::new(),
+                message: format!("Unsupported resolution: {}", s),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Timespan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -2,10 +2,11 @@ extern crate chrono;
 extern crate chrono_tz;
 extern crate polars;
 
+use crate::calendar::{NyseCalendar, TradingCalendar};
+use crate::error::{Error, ErrorContext, Result};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use chrono_tz::Tz;
 use polars::prelude::*;
-use rayon::prelude::*;
 
 pub struct Processor<'a, 'b> {
     pub df: &'a mut DataFrame,
@@ -17,11 +18,12 @@ impl<'a, 'b> Processor<'a, 'b> {
         Processor { df, market }
     }
 
-    pub fn process(&mut self) -> Result<(usize, usize), PolarsError> {
+    pub fn process(&mut self) -> Result<(usize, usize)> {
         let mut total_p1_outliers = 0;
         let mut total_p2_outliers = 0;
 
-        let processed_df = self.df.group_by(["mkt_date"])?.apply(|group_df| {
+        let mut processed_df = DataFrame::default();
+        for group_df in self.df.partition_by_stable(["mkt_date"], true)? {
             let mkt_date_column = group_df.column("mkt_date")?.str()?;
             let mkt_date = mkt_date_column.get(0).ok_or(PolarsError::NoData("mkt_date is empty".into()))?;
 
@@ -34,8 +36,8 @@ impl<'a, 'b> Processor<'a, 'b> {
             total_p1_outliers += p1_outliers;
             total_p2_outliers += p2_outliers;
 
-            Ok(filtered_df)
-        })?;
+            processed_df.vstack_mut(&filtered_df)?;
+        }
 
         *self.df = processed_df;
 
@@ -60,7 +62,7 @@ impl<'a> MADOutlierDetector<'a> {
         MADOutlierDetector { df, p1_p2_df }
     }
 
-    pub fn calculate_p1_p2(&mut self) -> Result<(), PolarsError> {
+    pub fn calculate_p1_p2(&mut self) -> Result<()> {
         let h = self.df.column("high")?.f64()?;
         let c = self.df.column("close")?.f64()?;
         let o = self.df.column("open")?.f64()?;
@@ -95,7 +97,7 @@ impl<'a> MADOutlierDetector<'a> {
         Ok(())
     }
 
-    fn detect_outliers(&mut self, column_name: &str, threshold: f64) -> Result<usize, PolarsError> {
+    fn detect_outliers(&mut self, column_name: &str, threshold: f64) -> Result<usize> {
         let column = self.p1_p2_df.column(column_name)?.f64()?;
         let median = column.median().unwrap_or_default();
 
@@ -121,7 +123,7 @@ impl<'a> MADOutlierDetector<'a> {
     }
 
 
-    pub fn normalize_outliers(&mut self, p_type: &str) -> Result<(), PolarsError> {
+    pub fn normalize_outliers(&mut self, p_type: &str) -> Result<()> {
         let outlier_col_name = format!("{}_outlier", p_type);
         let outlier_series = self.p1_p2_df.column(&outlier_col_name)?.f64()?;
 
@@ -137,7 +139,12 @@ impl<'a> MADOutlierDetector<'a> {
                     match p_type {
                         "p1" => adjust_value(open, close, &mut high_values, i, |o, c| o > c),
                         "p2" => adjust_value(open, close, &mut low_values, i, |o, c| o < c),
-                        _ => return Err(PolarsError::ComputeError("Invalid p_type provided".into())),
+                        _ => {
+                            return Err(Error::InvalidInput {
+                                context: ErrorContext::new(),
+                                message: format!("Invalid p_type provided: {}", p_type),
+                            })
+                        }
                     }
                 }
             }
@@ -150,7 +157,7 @@ impl<'a> MADOutlierDetector<'a> {
         Ok(())
     }
 
-    pub fn detect_normalize(&mut self) -> Result<(usize, usize), PolarsError> {
+    pub fn detect_normalize(&mut self) -> Result<(usize, usize)> {
         // Calculate p1 and p2
         self.calculate_p1_p2()?;
 
@@ -167,7 +174,7 @@ impl<'a> MADOutlierDetector<'a> {
     }
 }
 
-fn adjust_value<F>(open: Option<f64>, close: Option<f64>, values: &mut Vec<f64>, index: usize, comparator: F)
+fn adjust_value<F>(open: Option<f64>, close: Option<f64>, values: &mut [f64], index: usize, comparator: F)
     where F: Fn(f64, f64) -> bool {
     if let (Some(o), Some(c)) = (open, close) {
         values[index] = if comparator(o, c) { o } else { c };
@@ -201,23 +208,33 @@ impl MarketTimezone {
         }
     }
 
-    // Calculates and returns the start and end DateTime for a given date
-    pub fn market_hours_on_date(&self, date: &str) -> Result<(DateTime<Tz>, DateTime<Tz>), String> {
-        let naive_date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    // Returns the trading calendar of the market
+    pub fn calendar(&self) -> &'static dyn TradingCalendar {
+        match self {
+            MarketTimezone::Eastern => &NyseCalendar,
+            // Add other calendars as needed
+        }
+    }
+
+    // Calculates and returns the start and end DateTime for a given date, honouring early closes
+    pub fn market_hours_on_date(&self, date: &str) -> Result<(DateTime<Tz>, DateTime<Tz>)> {
+        let calendar_error = |message: String| Error::Calendar { context: ErrorContext::new().date(date), message };
+        let naive_date = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| calendar_error(e.to_string()))?;
         let (start_time, end_time) = self.working_hours();
+        let end_time = self.calendar().early_close(naive_date).unwrap_or(end_time);
         let timezone = self.timezone();
 
         let start_datetime = NaiveDateTime::new(naive_date, start_time);
         let end_datetime = NaiveDateTime::new(naive_date, end_time);
 
-        let start_tz_datetime = timezone.from_local_datetime(&start_datetime).single().ok_or_else(|| "Unable to determine unique timezone datetime for start".to_string())?;
-        let end_tz_datetime = timezone.from_local_datetime(&end_datetime).single().ok_or_else(|| "Unable to determine unique timezone datetime for end".to_string())?;
+        let start_tz_datetime = timezone.from_local_datetime(&start_datetime).single().ok_or_else(|| calendar_error("Unable to determine unique timezone datetime for start".to_string()))?;
+        let end_tz_datetime = timezone.from_local_datetime(&end_datetime).single().ok_or_else(|| calendar_error("Unable to determine unique timezone datetime for end".to_string()))?;
 
         Ok((start_tz_datetime, end_tz_datetime))
     }
 
     // Calculates and returns the start and end millisecond timestamp integers for a given date
-    pub fn market_hours_on_date_millis(&self, date: &str) -> Result<(i64, i64), String> {
+    pub fn market_hours_on_date_millis(&self, date: &str) -> Result<(i64, i64)> {
         let (start_tz_datetime, end_tz_datetime) = self.market_hours_on_date(date)?;
 
         let start_millis = start_tz_datetime.timestamp_millis();
@@ -238,16 +255,15 @@ impl<'a, 'b> MarketHoursFilter<'a, 'b> {
         MarketHoursFilter { df, market, date }
     }
 
-    pub fn filter(&self) -> Result<DataFrame, PolarsError> {
-        let (start_ts, end_ts) = self.market.market_hours_on_date_millis(self.date)
-            .map_err(|e| PolarsError::ComputeError(e.into()))?;
+    pub fn filter(&self) -> Result<DataFrame> {
+        let (start_ts, end_ts) = self.market.market_hours_on_date_millis(self.date)?;
 
         let time_column = self.df.column("time")?.i64()?;
         let mask = time_column.into_iter().map(|opt_time| {
             opt_time.map(|time| time >= start_ts && time <= end_ts).unwrap_or(false)
         }).collect::<BooleanChunked>();
 
-        self.df.filter(&mask)
+        Ok(self.df.filter(&mask)?)
     }
 }
 
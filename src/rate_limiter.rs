@@ -0,0 +1,293 @@
+// src/rate_limiter.rs
+
+use crate::error::{Error, ErrorContext, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep, Instant};
+
+/// Request rate presets for the Polygon subscription plans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RatePlan {
+    /// The free plan: 5 requests per minute.
+    Basic,
+    /// Polygon does not cap paid plans, but asks clients to stay under 100 requests per second.
+    Starter,
+    /// Same soft limit as `Starter`.
+    Developer,
+    /// No limit at all, e.g. for mock servers and replayed sessions.
+    Unlimited,
+}
+
+impl RatePlan {
+    /// Returns the plan's quota as (requests, period, max burst).
+    pub fn quota(&self) -> (u32, Duration, u32) {
+        match self {
+            RatePlan::Basic => (5, Duration::from_secs(60), 5),
+            RatePlan::Starter | RatePlan::Developer => (100, Duration::from_secs(1), 100),
+            RatePlan::Unlimited => (1, Duration::ZERO, 1),
+        }
+    }
+}
+
+/// A GCRA (generic cell rate algorithm) limiter, equivalent to a token bucket that refills
+/// continuously.
+///
+/// Each `acquire` reserves the next free slot with a single atomic update and then sleeps until
+/// that slot without holding any lock, so callers are served in the order they reserved. Cloning
+/// the limiter shares its state, which lets several sessions draw from one budget; a limiter
+/// created with `shared` keeps its state in a file, which lets several processes draw from one
+/// budget. A caller that is dropped while waiting still uses up its slot.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    state: Arc<LimiterState>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    /// Time between two requests at the sustained rate, in nanoseconds; zero when unlimited.
+    emission_interval: AtomicU64,
+    max_burst: u64,
+    /// The theoretical arrival time of the next request.
+    theoretical_arrival: ArrivalStore,
+}
+
+/// Where the theoretical arrival time lives.
+#[derive(Debug)]
+enum ArrivalStore {
+    /// In memory, in nanoseconds since `epoch`.
+    Memory { epoch: Instant, theoretical_arrival: AtomicU64 },
+    /// In a file, in nanoseconds since the Unix epoch, guarded by an advisory lock that is only
+    /// held while the value is read and written. The advisory lock belongs to the open file,
+    /// which every task of this process shares, so the mutex serializes the tasks.
+    File(Mutex<File>),
+}
+
+impl RateLimiter {
+    /// Allows `requests` every `period`, with bursts of up to `max_burst` requests.
+    pub fn new(requests: u32, period: Duration, max_burst: u32) -> Self {
+        let theoretical_arrival = ArrivalStore::Memory { epoch: Instant::now(), theoretical_arrival: AtomicU64::new(0) };
+        RateLimiter::with_store(requests, period, max_burst, theoretical_arrival)
+    }
+
+    /// Allows `requests` every `period` across every process using the same file.
+    ///
+    /// Processes sharing a file should use the same quota; each one spaces its own requests with
+    /// its own settings.
+    pub fn shared(path: impl AsRef<Path>, requests: u32, period: Duration, max_burst: u32) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| Error::Config { message: format!("Unable to open rate limit file {}: {}", path.display(), e) })?;
+        Ok(RateLimiter::with_store(requests, period, max_burst, ArrivalStore::File(Mutex::new(file))))
+    }
+
+    pub fn per_second(requests_per_second: u32, max_burst: u32) -> Self {
+        RateLimiter::new(requests_per_second, Duration::from_secs(1), max_burst)
+    }
+
+    pub fn unlimited() -> Self {
+        RateLimiter::new(1, Duration::ZERO, 1)
+    }
+
+    pub fn from_plan(plan: RatePlan) -> Self {
+        let (requests, period, max_burst) = plan.quota();
+        RateLimiter::new(requests, period, max_burst)
+    }
+
+    fn with_store(requests: u32, period: Duration, max_burst: u32, theoretical_arrival: ArrivalStore) -> Self {
+        let emission_interval = (period.as_nanos() / u128::from(requests.max(1))) as u64;
+        RateLimiter {
+            state: Arc::new(LimiterState {
+                emission_interval: AtomicU64::new(emission_interval),
+                max_burst: u64::from(max_burst.max(1)),
+                theoretical_arrival,
+            }),
+        }
+    }
+
+    /// Returns the sustained rate in requests per second; infinite when unlimited.
+    pub fn rate(&self) -> f64 {
+        match self.state.emission_interval.load(Ordering::Acquire) {
+            0 => f64::INFINITY,
+            emission_interval => 1e9 / emission_interval as f64,
+        }
+    }
+
+    /// Changes the sustained rate, in requests per second, keeping the burst size. Requests
+    /// already waiting keep the slot they reserved.
+    pub fn set_rate(&self, requests_per_second: f64) {
+        let emission_interval = if requests_per_second.is_finite() && requests_per_second > 0.0 {
+            (1e9 / requests_per_second).max(1.0) as u64
+        } else {
+            0
+        };
+        self.state.emission_interval.store(emission_interval, Ordering::Release);
+    }
+
+    /// Holds every request back for at least the given duration, e.g. to honour a `Retry-After`.
+    pub async fn pause(&self, duration: Duration) -> Result<()> {
+        let burst_tolerance = self.state.burst_tolerance();
+        self.update(move |theoretical_arrival, now| {
+            theoretical_arrival.max(now + duration.as_nanos() as u64 + burst_tolerance)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Waits until a request may be sent.
+    ///
+    /// Only fails if a shared limiter's file cannot be read or written.
+    pub async fn acquire(&self) -> Result<()> {
+        let state = &self.state;
+        let emission_interval = state.emission_interval.load(Ordering::Acquire);
+        let burst_tolerance = state.burst_tolerance();
+
+        // Unlimited limiters only wait out a pause
+        let next = move |theoretical_arrival: u64, now: u64| match emission_interval {
+            0 => theoretical_arrival,
+            _ => theoretical_arrival.max(now) + emission_interval,
+        };
+        let (theoretical_arrival, now) = self.update(next).await?;
+        let allowed_at = theoretical_arrival.max(now).saturating_sub(burst_tolerance);
+
+        if allowed_at > now {
+            sleep(Duration::from_nanos(allowed_at - now)).await;
+        }
+        Ok(())
+    }
+
+    /// Returns how many requests could be sent right now without waiting.
+    pub fn available_tokens(&self) -> Result<u32> {
+        let state = &self.state;
+        let emission_interval = state.emission_interval.load(Ordering::Acquire);
+        let (theoretical_arrival, now) = state.theoretical_arrival.load()?;
+        let backlog = theoretical_arrival.saturating_sub(now);
+        if emission_interval == 0 {
+            return Ok(if backlog == 0 { u32::MAX } else { 0 });
+        }
+
+        let capacity = state.burst_tolerance() + emission_interval;
+        Ok((capacity.saturating_sub(backlog) / emission_interval) as u32)
+    }
+
+    /// Updates the theoretical arrival time like `ArrivalStore::update`. Another process may hold
+    /// a shared limiter's file lock, so that wait happens off the runtime's threads.
+    async fn update(&self, next: impl Fn(u64, u64) -> u64 + Send + 'static) -> Result<(u64, u64)> {
+        match &self.state.theoretical_arrival {
+            ArrivalStore::Memory { .. } => self.state.theoretical_arrival.update(next),
+            ArrivalStore::File(_) => {
+                let state = Arc::clone(&self.state);
+                tokio::task::spawn_blocking(move || state.theoretical_arrival.update(next))
+                    .await
+                    .map_err(|e| file_error(std::io::Error::other(e)))?
+            }
+        }
+    }
+
+    /// Returns how long a request made now would wait.
+    pub fn wait_time(&self) -> Result<Duration> {
+        let state = &self.state;
+        let (theoretical_arrival, now) = state.theoretical_arrival.load()?;
+        let allowed_at = theoretical_arrival.saturating_sub(state.burst_tolerance());
+        Ok(Duration::from_nanos(allowed_at.saturating_sub(now)))
+    }
+}
+
+impl LimiterState {
+    /// How far ahead of the sustained rate a burst may run.
+    fn burst_tolerance(&self) -> u64 {
+        self.emission_interval.load(Ordering::Acquire).saturating_mul(self.max_burst - 1)
+    }
+}
+
+impl ArrivalStore {
+    /// Returns the theoretical arrival time and the current time.
+    fn load(&self) -> Result<(u64, u64)> {
+        match self {
+            ArrivalStore::Memory { epoch, theoretical_arrival } => {
+                Ok((theoretical_arrival.load(Ordering::Acquire), epoch.elapsed().as_nanos() as u64))
+            }
+            ArrivalStore::File(file) => {
+                let file = lock_file(file)?;
+                file.lock_shared().map_err(file_error)?;
+                let theoretical_arrival = read_arrival(&file);
+                let _ = file.unlock();
+                Ok((theoretical_arrival.map_err(file_error)?, unix_nanos()))
+            }
+        }
+    }
+
+    /// Atomically replaces the theoretical arrival time with `next(theoretical_arrival, now)` and
+    /// returns the previous value and the current time.
+    fn update(&self, next: impl Fn(u64, u64) -> u64) -> Result<(u64, u64)> {
+        match self {
+            ArrivalStore::Memory { epoch, theoretical_arrival } => {
+                let now = epoch.elapsed().as_nanos() as u64;
+                let mut current = theoretical_arrival.load(Ordering::Acquire);
+                loop {
+                    match theoretical_arrival.compare_exchange_weak(
+                        current,
+                        next(current, now),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(previous) => return Ok((previous, now)),
+                        Err(actual) => current = actual,
+                    }
+                }
+            }
+            ArrivalStore::File(file) => {
+                // The locks are held for one small read and write, never across a sleep
+                let file = lock_file(file)?;
+                file.lock_exclusive().map_err(file_error)?;
+                let now = unix_nanos();
+                let result = read_arrival(&file).and_then(|current| {
+                    write_arrival(&file, next(current, now))?;
+                    Ok((current, now))
+                });
+                let _ = file.unlock();
+                result.map_err(file_error)
+            }
+        }
+    }
+}
+
+fn lock_file(file: &Mutex<File>) -> Result<MutexGuard<'_, File>> {
+    file.lock().map_err(|_| file_error(std::io::Error::other("lock poisoned by a panicked task")))
+}
+
+fn read_arrival(mut file: &File) -> std::io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    file.seek(SeekFrom::Start(0))?;
+    let mut read = 0;
+    while read < bytes.len() {
+        match file.read(&mut bytes[read..])? {
+            0 => return Ok(0),
+            count => read += count,
+        }
+    }
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_arrival(mut file: &File, theoretical_arrival: u64) -> std::io::Result<()> {
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&theoretical_arrival.to_le_bytes())?;
+    file.flush()
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_nanos() as u64).unwrap_or(0)
+}
+
+fn file_error(e: std::io::Error) -> Error {
+    Error::Transport { context: ErrorContext::new(), message: format!("Rate limit file error: {}", e) }
+}
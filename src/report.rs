@@ -0,0 +1,191 @@
+// src/report.rs
+
+use crate::adaptive_rate::RateChange;
+use std::fmt;
+use std::time::Duration;
+
+/// The outcome of the requests made for a single date.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateStatus {
+    /// Polygon returned at least one bar.
+    Succeeded,
+    /// Polygon answered successfully but had no bars, e.g. when the ticker did not trade.
+    Empty,
+    /// Every attempt failed.
+    Failed,
+    /// The extraction was cancelled before the date finished.
+    Cancelled,
+}
+
+impl fmt::Display for DateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            DateStatus::Succeeded => "succeeded",
+            DateStatus::Empty => "empty",
+            DateStatus::Failed => "failed",
+            DateStatus::Cancelled => "cancelled",
+        };
+        f.write_str(status)
+    }
+}
+
+/// What happened when extracting a single date, or a multi-day window for coarse resolutions.
+#[derive(Clone, Debug)]
+pub struct DateReport {
+    /// The requested date, or `start..end` for a multi-day window.
+    pub date: String,
+    pub status: DateStatus,
+    /// Number of bars received, across all pages.
+    pub bars: usize,
+    /// Number of pages fetched by following `next_url`.
+    pub pages: usize,
+    /// Number of times the date was retried after its first attempt.
+    pub retries: u32,
+    /// Time taken by the last attempt.
+    pub latency: Duration,
+    /// The error of the last attempt, for failed dates.
+    pub error: Option<String>,
+}
+
+/// Per-date outcomes of extracting one ticker.
+#[derive(Clone, Debug, Default)]
+pub struct ExtractionReport {
+    pub ticker: String,
+    pub dates: Vec<DateReport>,
+    /// Changes the session's adaptive rate controller made while the ticker was extracted. The
+    /// controller is shared by the whole session, so this includes changes caused by other
+    /// requests made at the same time. A `TickerManagerPool` leaves it empty and lists the
+    /// changes once in `PoolReport::rate_changes`.
+    pub rate_changes: Vec<RateChange>,
+}
+
+impl ExtractionReport {
+    pub fn new(ticker: &str) -> Self {
+        ExtractionReport { ticker: ticker.to_string(), dates: Vec::new(), rate_changes: Vec::new() }
+    }
+
+    /// Returns the reports of the dates with the given status.
+    pub fn with_status(&self, status: DateStatus) -> impl Iterator<Item = &DateReport> {
+        self.dates.iter().filter(move |date| date.status == status)
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.with_status(DateStatus::Succeeded).count()
+    }
+
+    pub fn empty(&self) -> usize {
+        self.with_status(DateStatus::Empty).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.with_status(DateStatus::Failed).count()
+    }
+
+    pub fn total_bars(&self) -> usize {
+        self.dates.iter().map(|date| date.bars).sum()
+    }
+
+    pub fn cancelled(&self) -> usize {
+        self.with_status(DateStatus::Cancelled).count()
+    }
+
+    /// Returns the dates that failed or were cancelled, which a later run still has to fetch.
+    pub fn unfinished(&self) -> impl Iterator<Item = &DateReport> {
+        self.dates
+            .iter()
+            .filter(|date| matches!(date.status, DateStatus::Failed | DateStatus::Cancelled))
+    }
+
+    /// Returns true if every date finished and none failed.
+    pub fn is_complete(&self) -> bool {
+        self.unfinished().next().is_none()
+    }
+
+    pub(crate) fn sort(&mut self) {
+        self.dates.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+}
+
+impl fmt::Display for ExtractionReport {
+    /// Writes one tab separated line per date, suitable for an audit log.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for date in &self.dates {
+            writeln!(
+                f,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                self.ticker,
+                date.date,
+                date.status,
+                date.bars,
+                date.pages,
+                date.retries,
+                date.latency.as_millis(),
+                date.error.as_deref().unwrap_or("")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The extraction reports of every ticker in a `TickerManagerPool` run.
+#[derive(Clone, Debug, Default)]
+pub struct PoolReport {
+    pub reports: Vec<ExtractionReport>,
+    /// Changes the session's adaptive rate controller made during the run.
+    pub rate_changes: Vec<RateChange>,
+}
+
+impl PoolReport {
+    pub fn succeeded(&self) -> usize {
+        self.reports.iter().map(ExtractionReport::succeeded).sum()
+    }
+
+    pub fn empty(&self) -> usize {
+        self.reports.iter().map(ExtractionReport::empty).sum()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.reports.iter().map(ExtractionReport::failed).sum()
+    }
+
+    pub fn cancelled(&self) -> usize {
+        self.reports.iter().map(ExtractionReport::cancelled).sum()
+    }
+
+    pub fn total_bars(&self) -> usize {
+        self.reports.iter().map(ExtractionReport::total_bars).sum()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.reports.iter().all(ExtractionReport::is_complete)
+    }
+
+    /// Returns every failed date as a (ticker, date report) pair.
+    pub fn failed_dates(&self) -> Vec<(&str, &DateReport)> {
+        self.reports
+            .iter()
+            .flat_map(|report| {
+                report
+                    .with_status(DateStatus::Failed)
+                    .map(move |date| (report.ticker.as_str(), date))
+            })
+            .collect()
+    }
+
+    /// Returns every failed or cancelled date as a (ticker, date report) pair.
+    pub fn unfinished_dates(&self) -> Vec<(&str, &DateReport)> {
+        self.reports
+            .iter()
+            .flat_map(|report| report.unfinished().map(move |date| (report.ticker.as_str(), date)))
+            .collect()
+    }
+}
+
+impl fmt::Display for PoolReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in &self.reports {
+            write!(f, "{}", report)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+// src/retry.rs
+
+use crate::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+/// The kinds of failure that may succeed if the request is sent again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    /// The request could not be sent or its response could not be read.
+    Transport,
+    /// The request did not complete within `ExtractOptions::request_timeout`.
+    Timeout,
+    /// Polygon answered 429.
+    RateLimited,
+    /// Polygon answered with a 5xx status.
+    ServerError,
+    /// Polygon answered with an error payload.
+    Api,
+    /// The response body was not valid JSON, e.g. because it was truncated.
+    Decode,
+}
+
+impl RetryClass {
+    pub const ALL: [RetryClass; 6] = [
+        RetryClass::Transport,
+        RetryClass::Timeout,
+        RetryClass::RateLimited,
+        RetryClass::ServerError,
+        RetryClass::Api,
+        RetryClass::Decode,
+    ];
+}
+
+impl fmt::Display for RetryClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let class = match self {
+            RetryClass::Transport => "transport",
+            RetryClass::Timeout => "timeout",
+            RetryClass::RateLimited => "rate limited",
+            RetryClass::ServerError => "server error",
+            RetryClass::Api => "api",
+            RetryClass::Decode => "decode",
+        };
+        f.write_str(class)
+    }
+}
+
+/// How often, and how far apart, failed requests are sent again.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Attempts per date, including the first one.
+    pub max_attempts: u32,
+    /// The wait before the first retry; it doubles with every further retry.
+    pub base_backoff: Duration,
+    /// The longest wait between two attempts, unless Polygon asks for more with `Retry-After`.
+    pub max_backoff: Duration,
+    /// The fraction of each wait that is randomised, between 0 and 1, so clients that failed
+    /// together do not retry together. Values outside that range are clamped; NaN means none.
+    pub jitter: f64,
+    /// The failures worth retrying. Other retryable failures fail their date straight away;
+    /// errors that retrying cannot fix still fail the whole extraction.
+    pub retryable: Vec<RetryClass>,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy::default().max_attempts(1)
+    }
+
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn base_backoff(mut self, base_backoff: Duration) -> Self {
+        self.base_backoff = base_backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn retryable(mut self, retryable: &[RetryClass]) -> Self {
+        self.retryable = retryable.to_vec();
+        self
+    }
+
+    /// Returns true if the policy retries the given error.
+    pub fn should_retry(&self, error: &Error) -> bool {
+        error.retry_class().is_some_and(|class| self.retryable.contains(&class))
+    }
+
+    /// Returns how long to wait before the given retry, counting from 1, before jitter.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// Returns how long to wait before the given retry, with jitter applied.
+    pub fn jittered_backoff(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        // The field is public, so it may hold anything the builder would have clamped
+        let jitter = if self.jitter.is_nan() { 0.0 } else { self.jitter.clamp(0.0, 1.0) };
+        backoff.mul_f64(1.0 - jitter * rand::random::<f64>())
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 6,
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            jitter: 0.2,
+            retryable: RetryClass::ALL.to_vec(),
+        }
+    }
+}
@@ -1,77 +1,112 @@
 // src/session.rs 
 
 
-use lazy_static::lazy_static;
-use reqwest::{Client, Response};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
-use tokio::time::sleep;
 
-use crate::config::{MAX_BURST_REQUESTS, POLYGON_API_KEY, REQUESTS_PER_SECOND};
+use crate::adaptive_rate::{AdaptiveRate, AdaptiveRateController, RateChange};
+use crate::cache::ResponseCache;
+use crate::config::SessionConfig;
+use crate::error::Result;
+use crate::rate_limiter::RateLimiter;
+use crate::transport::{ReqwestTransport, Transport, TransportResponse};
 
 
-lazy_static! {
-    static ref SINGLETON_SESSION: Arc<Mutex<PolygonHistorySession >> = Arc::new(Mutex::new(PolygonHistorySession::new()));
-}
-
 pub struct PolygonHistorySession {
-    client: Client,
-    rate_limiter: Arc<Mutex<PolygonRateLimiter>>,
+    config: SessionConfig,
+    transport: Arc<dyn Transport>,
+    rate_limiter: RateLimiter,
+    cache: Option<ResponseCache>,
+    adaptive_rate: Option<AdaptiveRateController>,
 }
 
 impl PolygonHistorySession {
-    fn new() -> Self {
+    /// Creates a session that sends its requests over the network using the given config.
+    pub fn with_config(config: SessionConfig) -> Result<Self> {
+        let transport = ReqwestTransport::from_config(&config)?;
+        Ok(PolygonHistorySession::with_transport(config, Arc::new(transport)))
+    }
+
+    /// Creates a session that sends its requests through the given transport.
+    pub fn with_transport(config: SessionConfig, transport: Arc<dyn Transport>) -> Self {
+        let rate_limiter = RateLimiter::per_second(config.requests_per_second, config.max_burst_requests);
         PolygonHistorySession {
-            client: Client::new(),
-            rate_limiter: Arc::new(Mutex::new(PolygonRateLimiter::new(REQUESTS_PER_SECOND))),
+            config,
+            transport,
+            rate_limiter,
+            cache: None,
+            adaptive_rate: None,
         }
     }
 
-    pub async fn send_request(url: &str) -> Result<Response, reqwest::Error> {
-        let session = Arc::clone(&SINGLETON_SESSION);
-        let url_with_api_key = format!("{}&apiKey={}", url, POLYGON_API_KEY);
+    /// Serves and stores aggregate responses through the given on-disk cache.
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 
-        let (client, rate_limiter) = {
-            let session = session.lock().await;
-            (session.client.clone(), Arc::clone(&session.rate_limiter))
-        };
+    /// Draws requests from the given limiter instead of one built from the config. Pass clones
+    /// of one limiter to several sessions to share a single budget between them.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        if let Some(controller) = &self.adaptive_rate {
+            controller.clamp(&rate_limiter);
+        }
+        self.rate_limiter = rate_limiter;
+        self
+    }
 
-        rate_limiter.lock().await.acquire().await;
+    /// Adapts the request rate to Polygon's responses within the given bounds, starting from the
+    /// configured rate.
+    pub fn with_adaptive_rate(mut self, adaptive_rate: AdaptiveRate) -> Self {
+        let controller = AdaptiveRateController::new(adaptive_rate);
+        controller.clamp(&self.rate_limiter);
+        self.adaptive_rate = Some(controller);
+        self
+    }
 
-        client.get(&url_with_api_key).send().await
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
     }
-}
 
-struct PolygonRateLimiter {
-    tokens: u32,
-    last_refill_time: Instant,
-    refill_interval: Duration,
-}
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
 
-impl PolygonRateLimiter {
-    fn new(requests_per_second: u32) -> Self {
-        PolygonRateLimiter {
-            tokens: MAX_BURST_REQUESTS,
-            last_refill_time: Instant::now(),
-            refill_interval: Duration::from_secs(1) / requests_per_second,
-        }
+    pub fn rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
     }
 
-    async fn acquire(&mut self) {
-        while self.tokens == 0 {
-            let now = Instant::now();
-            let elapsed = now - self.last_refill_time;
-
-            if elapsed >= self.refill_interval {
-                let refill_count = (elapsed.as_secs_f32() / self.refill_interval.as_secs_f32()) as u32;
-                self.tokens = std::cmp::min(self.tokens + refill_count, MAX_BURST_REQUESTS);
-                self.last_refill_time = now;
-            } else {
-                sleep(self.refill_interval - elapsed).await;
-            }
-        }
+    /// Returns every change the adaptive rate controller made, oldest first.
+    pub fn rate_changes(&self) -> Vec<RateChange> {
+        self.adaptive_rate.as_ref().map(AdaptiveRateController::changes).unwrap_or_default()
+    }
 
-        self.tokens -= 1;
+    /// Returns the rate changes after the first `skip`, for reports covering part of the session.
+    pub(crate) fn rate_changes_since(&self, skip: usize) -> Vec<RateChange> {
+        self.adaptive_rate
+            .as_ref()
+            .map(|controller| controller.changes_since(skip))
+            .unwrap_or_default()
     }
-}
\ No newline at end of file
+
+    /// Sends a GET request for the given URL.
+    ///
+    /// Relative paths are resolved against the configured base URL; absolute URLs, such as
+    /// Polygon's `next_url`, are used as-is.
+    pub async fn send_request(&self, url: &str) -> Result<TransportResponse> {
+        let url = if url.starts_with("http://") || url.starts_with("https://") {
+            url.to_string()
+        } else {
+            format!("{}{}", self.config.base_url, url)
+        };
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let url_with_api_key = format!("{}{}apiKey={}", url, separator, self.config.api_key);
+
+        self.rate_limiter.acquire().await?;
+
+        let response = self.transport.get(&url_with_api_key).await?;
+        if let Some(controller) = &self.adaptive_rate {
+            controller.observe(&response, &self.rate_limiter).await?;
+        }
+        Ok(response)
+    }
+}
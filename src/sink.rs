@@ -0,0 +1,62 @@
+// src/sink.rs
+
+use crate::error::{Error, ErrorContext, Result};
+use async_trait::async_trait;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// A destination for processed bars, such as a directory of Parquet files.
+#[async_trait]
+pub trait Sink: Send + Sync {
+    /// Writes processed bars of one or more tickers. Writing bars that are already stored
+    /// replaces them.
+    async fn write(&self, bars: &DataFrame) -> Result<()>;
+}
+
+/// The bars of one ticker in one calendar month of `mkt_date`.
+pub(crate) struct MonthPartition {
+    pub ticker: String,
+    pub year: String,
+    pub month: String,
+    pub bars: DataFrame,
+}
+
+impl MonthPartition {
+    /// Returns the hive style directory of the partition, `ticker=AAPL/year=2023/month=01`.
+    pub fn dir(&self, root: &Path) -> PathBuf {
+        ticker_dir(root, &self.ticker).join(format!("year={}", self.year)).join(format!("month={}", self.month))
+    }
+}
+
+/// Returns the directory holding every partition of the ticker.
+pub(crate) fn ticker_dir(root: &Path, ticker: &str) -> PathBuf {
+    root.join(format!("ticker={}", urlencoding::encode(ticker)))
+}
+
+/// Splits bars into one frame per ticker and month of `mkt_date`, keeping their order.
+pub(crate) fn partition_by_month(bars: &DataFrame) -> Result<Vec<MonthPartition>> {
+    if bars.height() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let months: Vec<Option<&str>> = bars.column("mkt_date")?.str()?.into_iter().map(|date| date.map(|date| &date[..7])).collect();
+    let mut keyed = bars.clone();
+    keyed.with_column(Series::new("_month", months))?;
+
+    let mut partitions = Vec::new();
+    for group in keyed.partition_by_stable(["ticker", "_month"], true)? {
+        let ticker = group.column("ticker")?.str()?.get(0).unwrap_or_default().to_string();
+        let month = group.column("_month")?.str()?.get(0).unwrap_or_default().to_string();
+        let (year, month) = month.split_once('-').ok_or_else(|| Error::Store {
+            context: ErrorContext::new().ticker(&ticker),
+            message: format!("Invalid mkt_date month: {}", month),
+        })?;
+        partitions.push(MonthPartition {
+            ticker: ticker.clone(),
+            year: year.to_string(),
+            month: month.to_string(),
+            bars: group.drop("_month")?,
+        });
+    }
+    Ok(partitions)
+}
@@ -0,0 +1,313 @@
+// src/sql_store.rs
+//
+// Row conversion, statements and the store implementation shared by the SQLite and DuckDB
+// stores, whose drivers expose the same API.
+
+use crate::error::{Error, ErrorContext, Result};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The columns of the bars table, in the order `upsert` binds them.
+const COLUMNS: &str = "ticker, multiplier, timespan, time, mkt_date, open, high, low, close, volume, vwap, transactions";
+
+/// How many bars one upsert statement writes. Twelve parameters per bar stays well below the
+/// drivers' parameter limits.
+pub(crate) const UPSERT_BATCH: usize = 1000;
+
+/// Returns a statement inserting `rows` bars, replacing the stored bars with the same key.
+pub(crate) fn upsert(rows: usize) -> String {
+    let values = vec!["(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"; rows].join(", ");
+    format!(
+        "INSERT INTO bars ({}) VALUES {}
+        ON CONFLICT (ticker, multiplier, timespan, time) DO UPDATE SET
+            mkt_date = excluded.mkt_date,
+            open = excluded.open,
+            high = excluded.high,
+            low = excluded.low,
+            close = excluded.close,
+            volume = excluded.volume,
+            vwap = excluded.vwap,
+            transactions = excluded.transactions",
+        COLUMNS, values
+    )
+}
+
+pub(crate) const SELECT_LAST_DATE: &str =
+    "SELECT MAX(mkt_date) FROM bars WHERE ticker = ? AND multiplier = ? AND timespan = ?";
+
+/// Selects bars in the column order of `Bar`.
+pub(crate) const SELECT_RANGE: &str = "
+    SELECT volume, vwap, open, close, high, low, time, transactions, mkt_date, ticker
+    FROM bars
+    WHERE ticker = ? AND multiplier = ? AND timespan = ? AND mkt_date BETWEEN ? AND ?
+    ORDER BY time";
+
+/// One processed bar, as stored in a row of the bars table.
+pub(crate) struct Bar {
+    pub volume: Option<i64>,
+    pub vwap: Option<f64>,
+    pub open: Option<f64>,
+    pub close: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub time: i64,
+    pub transactions: Option<i64>,
+    pub mkt_date: String,
+    pub ticker: String,
+}
+
+/// Splits processed bars into rows.
+pub(crate) fn bars_from_frame(df: &DataFrame) -> Result<Vec<Bar>> {
+    if df.height() == 0 {
+        return Ok(Vec::new());
+    }
+
+    let volume = df.column("volume")?.i64()?;
+    let vwap = df.column("vwap")?.f64()?;
+    let open = df.column("open")?.f64()?;
+    let close = df.column("close")?.f64()?;
+    let high = df.column("high")?.f64()?;
+    let low = df.column("low")?.f64()?;
+    let time = df.column("time")?.i64()?;
+    let transactions = df.column("transactions")?.i64()?;
+    let mkt_date = df.column("mkt_date")?.str()?;
+    let ticker = df.column("ticker")?.str()?;
+
+    (0..df.height())
+        .map(|i| {
+            let (Some(time), Some(mkt_date), Some(ticker)) = (time.get(i), mkt_date.get(i), ticker.get(i)) else {
+                return Err(Error::Store {
+                    context: ErrorContext::new(),
+                    message: format!("Bar {} has no time, mkt_date or ticker", i),
+                });
+            };
+            Ok(Bar {
+                volume: volume.get(i),
+                vwap: vwap.get(i),
+                open: open.get(i),
+                close: close.get(i),
+                high: high.get(i),
+                low: low.get(i),
+                time,
+                transactions: transactions.get(i),
+                mkt_date: mkt_date.to_string(),
+                ticker: ticker.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Keeps the last of the bars sharing a `ticker` and `time`, since a statement may not update
+/// the same row twice.
+pub(crate) fn unique_bars(bars: Vec<Bar>) -> Vec<Bar> {
+    let mut positions = HashMap::with_capacity(bars.len());
+    let mut unique: Vec<Bar> = Vec::with_capacity(bars.len());
+    for bar in bars {
+        match positions.get(&(bar.ticker.clone(), bar.time)) {
+            Some(&position) => unique[position] = bar,
+            None => {
+                positions.insert((bar.ticker.clone(), bar.time), unique.len());
+                unique.push(bar);
+            }
+        }
+    }
+    unique
+}
+
+/// Builds a frame with the extractor's columns and types from rows.
+pub(crate) fn frame_from_bars(bars: Vec<Bar>) -> Result<DataFrame> {
+    let df = DataFrame::new(vec![
+        Series::new("volume", bars.iter().map(|bar| bar.volume).collect::<Vec<_>>()),
+        Series::new("vwap", bars.iter().map(|bar| bar.vwap).collect::<Vec<_>>()),
+        Series::new("open", bars.iter().map(|bar| bar.open).collect::<Vec<_>>()),
+        Series::new("close", bars.iter().map(|bar| bar.close).collect::<Vec<_>>()),
+        Series::new("high", bars.iter().map(|bar| bar.high).collect::<Vec<_>>()),
+        Series::new("low", bars.iter().map(|bar| bar.low).collect::<Vec<_>>()),
+        Series::new("time", bars.iter().map(|bar| bar.time).collect::<Vec<_>>()),
+        Series::new("transactions", bars.iter().map(|bar| bar.transactions).collect::<Vec<_>>()),
+        Series::new("mkt_date", bars.iter().map(|bar| bar.mkt_date.as_str()).collect::<Vec<_>>()),
+        Series::new("ticker", bars.iter().map(|bar| bar.ticker.as_str()).collect::<Vec<_>>()),
+    ])?;
+    Ok(df)
+}
+
+pub(crate) fn store_error(path: &Path, context: ErrorContext, error: impl std::fmt::Display) -> Error {
+    Error::Store { context: context.url(path.display().to_string()), message: error.to_string() }
+}
+
+/// Defines a `BarStore` and `Sink` keeping bars in a `bars` table, for a driver crate with the
+/// rusqlite API: `impl_sql_store!(/// docs, StoreName, driver_crate, SCHEMA)`.
+macro_rules! impl_sql_store {
+    ($(#[$attr:meta])* $name:ident, $driver:ident, $schema:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            path: std::path::PathBuf,
+            connection: std::sync::Arc<std::sync::Mutex<$driver::Connection>>,
+            multiplier: u32,
+            timespan: $crate::poly_agg_info::Timespan,
+        }
+
+        impl $name {
+            /// Opens the database at the path, creating it and the bars table if needed.
+            pub fn open(
+                path: impl Into<std::path::PathBuf>,
+                multiplier: u32,
+                timespan: $crate::poly_agg_info::Timespan,
+            ) -> $crate::error::Result<Self> {
+                let path = path.into();
+                let connection = $driver::Connection::open(&path)
+                    .map_err(|e| $crate::sql_store::store_error(&path, $crate::error::ErrorContext::new(), e))?;
+                Self::with_connection(connection, path, multiplier, timespan)
+            }
+
+            /// Opens a database that lives in memory until the store is dropped.
+            pub fn in_memory(multiplier: u32, timespan: $crate::poly_agg_info::Timespan) -> $crate::error::Result<Self> {
+                let path = std::path::PathBuf::from(":memory:");
+                let connection = $driver::Connection::open_in_memory()
+                    .map_err(|e| $crate::sql_store::store_error(&path, $crate::error::ErrorContext::new(), e))?;
+                Self::with_connection(connection, path, multiplier, timespan)
+            }
+
+            fn with_connection(
+                connection: $driver::Connection,
+                path: std::path::PathBuf,
+                multiplier: u32,
+                timespan: $crate::poly_agg_info::Timespan,
+            ) -> $crate::error::Result<Self> {
+                connection
+                    .execute_batch($schema)
+                    .map_err(|e| $crate::sql_store::store_error(&path, $crate::error::ErrorContext::new(), e))?;
+                Ok($name {
+                    path,
+                    connection: std::sync::Arc::new(std::sync::Mutex::new(connection)),
+                    multiplier,
+                    timespan,
+                })
+            }
+
+            pub fn path(&self) -> &std::path::Path {
+                &self.path
+            }
+
+            /// Runs a database call on the blocking thread pool.
+            async fn run<T, F>(&self, context: $crate::error::ErrorContext, call: F) -> $crate::error::Result<T>
+            where
+                T: Send + 'static,
+                F: FnOnce(&mut $driver::Connection) -> $driver::Result<T> + Send + 'static,
+            {
+                let connection = std::sync::Arc::clone(&self.connection);
+                let path = self.path.clone();
+                tokio::task::spawn_blocking(move || {
+                    // A call that panicked may have left a transaction open, so refuse to go on
+                    let mut connection = connection.lock().map_err(|_| {
+                        $crate::sql_store::store_error(&path, context.clone(), "a call panicked while holding the connection")
+                    })?;
+                    call(&mut connection).map_err(|e| $crate::sql_store::store_error(&path, context, e))
+                })
+                .await
+                .map_err(|e| $crate::error::Error::Store {
+                    context: $crate::error::ErrorContext::new(),
+                    message: e.to_string(),
+                })?
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::store::BarStore for $name {
+            async fn last_date(&self, ticker: &str) -> $crate::error::Result<Option<chrono::NaiveDate>> {
+                let (ticker, multiplier, timespan) = (ticker.to_string(), self.multiplier, self.timespan.as_str());
+                let context = $crate::error::ErrorContext::new().ticker(&ticker);
+                let last: Option<String> = self
+                    .run(context.clone(), move |connection| {
+                        connection.query_row(
+                            $crate::sql_store::SELECT_LAST_DATE,
+                            $driver::params![ticker, multiplier, timespan],
+                            |row| row.get(0),
+                        )
+                    })
+                    .await?;
+
+                last.map(|date| {
+                    chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| $crate::error::Error::Store {
+                        context: context.date(&date),
+                        message: e.to_string(),
+                    })
+                })
+                .transpose()
+            }
+
+            async fn append(&self, bars: &polars::prelude::DataFrame) -> $crate::error::Result<()> {
+                let bars = $crate::sql_store::unique_bars($crate::sql_store::bars_from_frame(bars)?);
+                let (multiplier, timespan) = (self.multiplier, self.timespan.as_str());
+                self.run($crate::error::ErrorContext::new(), move |connection| {
+                    let transaction = connection.transaction()?;
+                    for batch in bars.chunks($crate::sql_store::UPSERT_BATCH) {
+                        let mut statement = transaction.prepare_cached(&$crate::sql_store::upsert(batch.len()))?;
+                        let mut params: Vec<&dyn $driver::ToSql> = Vec::with_capacity(batch.len() * 12);
+                        for bar in batch {
+                            params.extend([
+                                &bar.ticker as &dyn $driver::ToSql,
+                                &multiplier,
+                                &timespan,
+                                &bar.time,
+                                &bar.mkt_date,
+                                &bar.open,
+                                &bar.high,
+                                &bar.low,
+                                &bar.close,
+                                &bar.volume,
+                                &bar.vwap,
+                                &bar.transactions,
+                            ]);
+                        }
+                        statement.execute(&params[..])?;
+                    }
+                    transaction.commit()
+                })
+                .await
+            }
+
+            async fn read(
+                &self,
+                ticker: &str,
+                range: std::ops::RangeInclusive<chrono::NaiveDate>,
+            ) -> $crate::error::Result<polars::prelude::DataFrame> {
+                let (ticker, multiplier, timespan) = (ticker.to_string(), self.multiplier, self.timespan.as_str());
+                let (start, end) = $crate::store::date_bounds(&range);
+                let bars = self
+                    .run($crate::error::ErrorContext::new().ticker(&ticker), move |connection| {
+                        let mut statement = connection.prepare_cached($crate::sql_store::SELECT_RANGE)?;
+                        let rows =
+                            statement.query_map($driver::params![ticker, multiplier, timespan, start, end], |row| {
+                                Ok($crate::sql_store::Bar {
+                                    volume: row.get(0)?,
+                                    vwap: row.get(1)?,
+                                    open: row.get(2)?,
+                                    close: row.get(3)?,
+                                    high: row.get(4)?,
+                                    low: row.get(5)?,
+                                    time: row.get(6)?,
+                                    transactions: row.get(7)?,
+                                    mkt_date: row.get(8)?,
+                                    ticker: row.get(9)?,
+                                })
+                            })?;
+                        rows.collect::<$driver::Result<Vec<$crate::sql_store::Bar>>>()
+                    })
+                    .await?;
+                $crate::sql_store::frame_from_bars(bars)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::sink::Sink for $name {
+            async fn write(&self, bars: &polars::prelude::DataFrame) -> $crate::error::Result<()> {
+                $crate::store::BarStore::append(self, bars).await
+            }
+        }
+    };
+}
+
+pub(crate) use impl_sql_store;
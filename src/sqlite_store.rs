@@ -0,0 +1,32 @@
+// src/sqlite_store.rs
+
+use crate::sql_store::impl_sql_store;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS bars (
+        ticker TEXT NOT NULL,
+        multiplier INTEGER NOT NULL,
+        timespan TEXT NOT NULL,
+        time INTEGER NOT NULL,
+        mkt_date TEXT NOT NULL,
+        open REAL,
+        high REAL,
+        low REAL,
+        close REAL,
+        volume INTEGER,
+        vwap REAL,
+        transactions INTEGER,
+        PRIMARY KEY (ticker, multiplier, timespan, time)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS bars_by_mkt_date ON bars (ticker, multiplier, timespan, mkt_date);";
+
+impl_sql_store!(
+    /// Keeps processed bars in a SQLite database, in a `bars` table keyed on
+    /// `(ticker, multiplier, timespan, time)`.
+    ///
+    /// A store reads and writes the bars of one resolution, so several stores can share a database.
+    /// Writing a bar that is already stored replaces it, so reprocessed days overwrite their old rows.
+    SqliteStore,
+    rusqlite,
+    SCHEMA
+);
@@ -0,0 +1,153 @@
+// src/store.rs
+
+use crate::error::{Error, ErrorContext, Result};
+use crate::sink::Sink;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::ops::RangeInclusive;
+use std::sync::Mutex;
+
+/// The columns of processed bars, in the order the extractor returns them.
+pub const BAR_COLUMNS: [&str; 10] =
+    ["volume", "vwap", "open", "close", "high", "low", "time", "transactions", "mkt_date", "ticker"];
+
+/// Where processed bars are kept between runs, for a single resolution.
+#[async_trait]
+pub trait BarStore: Send + Sync {
+    /// Returns the latest `mkt_date` stored for the ticker; none if it has no bars yet.
+    async fn last_date(&self, ticker: &str) -> Result<Option<NaiveDate>>;
+
+    /// Adds processed bars to the store. Bars with the same `ticker` and `time` as a stored bar
+    /// replace it.
+    async fn append(&self, bars: &DataFrame) -> Result<()>;
+
+    /// Returns the ticker's bars whose `mkt_date` falls in the range, sorted by time, with the
+    /// columns and types of the extractor's output.
+    async fn read(&self, ticker: &str, range: RangeInclusive<NaiveDate>) -> Result<DataFrame>;
+}
+
+/// A `BarStore` that keeps every bar in memory, for tests and short-lived pipelines.
+#[derive(Debug, Default)]
+pub struct MemoryBarStore {
+    bars: Mutex<DataFrame>,
+}
+
+impl MemoryBarStore {
+    pub fn new() -> Self {
+        MemoryBarStore::default()
+    }
+
+    /// Returns a copy of every stored bar, sorted by ticker and time.
+    pub fn bars(&self) -> DataFrame {
+        self.bars.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl BarStore for MemoryBarStore {
+    async fn last_date(&self, ticker: &str) -> Result<Option<NaiveDate>> {
+        last_date(&self.bars.lock().unwrap(), ticker)
+    }
+
+    async fn append(&self, bars: &DataFrame) -> Result<()> {
+        let mut stored = self.bars.lock().unwrap();
+        *stored = merge(&stored, bars)?;
+        Ok(())
+    }
+
+    async fn read(&self, ticker: &str, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+        select_range(&self.bars.lock().unwrap(), ticker, range)
+    }
+}
+
+#[async_trait]
+impl Sink for MemoryBarStore {
+    async fn write(&self, bars: &DataFrame) -> Result<()> {
+        self.append(bars).await
+    }
+}
+
+/// Returns an empty frame with the extractor's columns and types.
+pub(crate) fn empty_bars() -> DataFrame {
+    let schema = Schema::from_iter(vec![
+        Field::new("volume", DataType::Int64),
+        Field::new("vwap", DataType::Float64),
+        Field::new("open", DataType::Float64),
+        Field::new("close", DataType::Float64),
+        Field::new("high", DataType::Float64),
+        Field::new("low", DataType::Float64),
+        Field::new("time", DataType::Int64),
+        Field::new("transactions", DataType::Int64),
+        Field::new("mkt_date", DataType::String),
+        Field::new("ticker", DataType::String),
+    ]);
+    DataFrame::from(&schema)
+}
+
+/// Formats the range as `mkt_date` strings. Dates outside years 1 to 9999 are clamped, since their
+/// years are not four digits and would not compare correctly as strings.
+pub(crate) fn date_bounds(range: &RangeInclusive<NaiveDate>) -> (String, String) {
+    let (min, max) = (NaiveDate::from_ymd_opt(1, 1, 1).unwrap(), NaiveDate::from_ymd_opt(9999, 12, 31).unwrap());
+    let format = |date: &NaiveDate| date.clamp(&min, &max).format("%Y-%m-%d").to_string();
+    (format(range.start()), format(range.end()))
+}
+
+/// Returns the ticker's bars whose `mkt_date` falls in the range, sorted by time.
+pub(crate) fn select_range(df: &DataFrame, ticker: &str, range: RangeInclusive<NaiveDate>) -> Result<DataFrame> {
+    if df.height() == 0 {
+        return Ok(empty_bars());
+    }
+
+    let (start, end) = date_bounds(&range);
+    let selected = df
+        .clone()
+        .lazy()
+        .filter(
+            col("ticker")
+                .eq(lit(ticker))
+                .and(col("mkt_date").gt_eq(lit(start)))
+                .and(col("mkt_date").lt_eq(lit(end))),
+        )
+        .sort(["time"], SortMultipleOptions::default())
+        .select(BAR_COLUMNS.map(col))
+        .collect()?;
+    Ok(selected)
+}
+
+/// Returns the latest `mkt_date` of the ticker's bars in the frame.
+pub(crate) fn last_date(df: &DataFrame, ticker: &str) -> Result<Option<NaiveDate>> {
+    if df.height() == 0 {
+        return Ok(None);
+    }
+
+    let tickers = df.column("ticker")?.str()?;
+    let mkt_dates = df.column("mkt_date")?.str()?;
+    let last = tickers
+        .into_iter()
+        .zip(mkt_dates)
+        .filter(|(bar_ticker, _)| *bar_ticker == Some(ticker))
+        .filter_map(|(_, mkt_date)| mkt_date)
+        .max();
+
+    last.map(|mkt_date| {
+        NaiveDate::parse_from_str(mkt_date, "%Y-%m-%d").map_err(|e| Error::Store {
+            context: ErrorContext::new().ticker(ticker).date(mkt_date),
+            message: e.to_string(),
+        })
+    })
+    .transpose()
+}
+
+/// Stacks new bars under the stored ones, keeping the newest bar for each `(ticker, time)`.
+pub(crate) fn merge(stored: &DataFrame, bars: &DataFrame) -> Result<DataFrame> {
+    let mut merged = if stored.width() == 0 { bars.clone() } else { stored.vstack(bars)? };
+    if merged.height() == 0 {
+        return Ok(merged);
+    }
+
+    let subset = ["ticker".to_string(), "time".to_string()];
+    merged = merged.unique_stable(Some(&subset), UniqueKeepStrategy::Last, None)?;
+    merged.sort_in_place(["ticker", "time"], SortMultipleOptions::default())?;
+    Ok(merged)
+}
@@ -1,51 +1,142 @@
 // src/ticker_manager.rs
 
-use crate::minute_extractor::MinuteExtractor;
+use crate::agg_extractor::AggExtractor;
+use crate::checkpoint::CheckpointStore;
+use crate::error::Result;
+use crate::extract_options::ExtractOptions;
 use crate::poly_agg_info::PolyAggInfo;
 use crate::processor::Processor;
 use crate::processor::MarketTimezone;
+use crate::report::ExtractionReport;
+use crate::session::PolygonHistorySession;
+use crate::sink::Sink;
 use polars::prelude::*;
 use async_trait::async_trait;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 pub struct TickerManager {
     poly_agg_info: PolyAggInfo,
+    session: Arc<PolygonHistorySession>,
+    options: ExtractOptions,
+    checkpoints: Option<CheckpointStore>,
+    sink: Option<Arc<dyn Sink>>,
 }
 
 impl TickerManager {
-    pub fn new(poly_agg_info: PolyAggInfo) -> Self {
-        TickerManager { poly_agg_info }
+    pub fn new(poly_agg_info: PolyAggInfo, session: Arc<PolygonHistorySession>) -> Self {
+        TickerManager { poly_agg_info, session, options: ExtractOptions::default(), checkpoints: None, sink: None }
     }
 
-    pub async fn process_data(&self) -> Result<DataFrame, PolarsError> {
+    /// Extracts with the given concurrency, timeout and retry settings.
+    pub fn with_options(mut self, options: ExtractOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Only fetches the dates the store has not recorded as completed.
+    ///
+    /// With a sink, the dates are recorded once the sink has written their bars. Without one,
+    /// nothing is recorded until the caller has kept the bars and calls `mark_completed`.
+    pub fn with_checkpoints(mut self, checkpoints: CheckpointStore) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    /// Writes the processed bars to the sink before returning them.
+    pub fn with_sink(mut self, sink: Arc<dyn Sink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub async fn process_data(&self) -> Result<DataFrame> {
         // 1. Upload the data using the correct Strategy based on the resolution value
-        let strategy = self.create_strategy();
-        let mut df = strategy.extract_data().await?;
+        let strategy = self.create_strategy()?;
+        let (df, report) = strategy.extract_data().await?;
 
         // 2. Use the Processor struct to process the uploaded data
+        let df = self.process(df)?;
+
+        // 3. Save the resulting DataFrame to the sink, if any
+        self.save(strategy.as_ref(), &df, &report).await?;
+        Ok(df)
+    }
+
+    /// Like `process_data`, but dates that fail after all retries are recorded in the returned
+    /// report rather than failing the whole ticker.
+    pub async fn process_data_with_report(&self) -> Result<(DataFrame, ExtractionReport)> {
+        self.process_data_with_cancellation(CancellationToken::new()).await
+    }
+
+    /// Like `process_data_with_report`, but stops once `cancel` is cancelled and processes the
+    /// bars fetched so far. Unfinished dates are reported as cancelled.
+    pub async fn process_data_with_cancellation(&self, cancel: CancellationToken) -> Result<(DataFrame, ExtractionReport)> {
+        let strategy = self.create_strategy()?;
+        let (df, report) = strategy.extract_data_with_cancellation(cancel).await?;
+        let df = self.process(df)?;
+        self.save(strategy.as_ref(), &df, &report).await?;
+        Ok((df, report))
+    }
+
+    /// Records the dates of the report that succeeded or had no bars as completed, for callers
+    /// that keep the bars themselves rather than through a sink. Does nothing without a
+    /// checkpoint store.
+    pub async fn mark_completed(&self, report: &ExtractionReport) -> Result<()> {
+        self.create_strategy()?.mark_completed(report).await
+    }
+
+    /// Filters intraday bars to regular market hours and normalizes their outliers. Hour and
+    /// coarser bars are returned as extracted.
+    fn process(&self, mut df: DataFrame) -> Result<DataFrame> {
+        if df.height() == 0 || !self.poly_agg_info.timespan()?.is_intraday() {
+            return Ok(df);
+        }
+
         let market_timezone = MarketTimezone::Eastern;
         let mut processor = Processor::new(&mut df, &market_timezone);
         let _ = processor.process()?;
 
-        // 3. Store the resulting DataFrame for saving afterwards
-        Ok(processor.df.clone())
+        Ok(df)
     }
 
-    fn create_strategy(&self) -> Box<dyn Strategy> {
-        match self.poly_agg_info.resolution.as_str() {
-            "minute" => Box::new(MinuteExtractor::new(self.poly_agg_info.clone())),
-            _ => panic!("Unsupported resolution"),
+    /// Writes the bars to the sink, if any, and only then checkpoints their dates.
+    async fn save(&self, strategy: &dyn Strategy, df: &DataFrame, report: &ExtractionReport) -> Result<()> {
+        let Some(sink) = &self.sink else {
+            return Ok(());
+        };
+        if df.height() > 0 {
+            sink.write(df).await?;
         }
+        strategy.mark_completed(report).await
+    }
+
+    fn create_strategy(&self) -> Result<Box<dyn Strategy>> {
+        self.poly_agg_info.timespan()?;
+        let mut strategy = AggExtractor::new(self.poly_agg_info.clone(), Arc::clone(&self.session));
+        strategy.extractor.options = self.options.clone();
+        strategy.extractor.checkpoints = self.checkpoints.clone();
+        Ok(Box::new(strategy))
     }
 }
 
 #[async_trait]
-trait Strategy {
-    async fn extract_data(&self) -> Result<DataFrame, PolarsError>;
+trait Strategy: Send + Sync {
+    async fn extract_data(&self) -> Result<(DataFrame, ExtractionReport)>;
+    async fn extract_data_with_cancellation(&self, cancel: CancellationToken) -> Result<(DataFrame, ExtractionReport)>;
+    async fn mark_completed(&self, report: &ExtractionReport) -> Result<()>;
 }
 
 #[async_trait]
-impl Strategy for MinuteExtractor {
-    async fn extract_data(&self) -> Result<DataFrame, PolarsError> {
-        self.extractor.extract().await
+impl Strategy for AggExtractor {
+    async fn extract_data(&self) -> Result<(DataFrame, ExtractionReport)> {
+        self.extractor.extract_complete().await
+    }
+
+    async fn extract_data_with_cancellation(&self, cancel: CancellationToken) -> Result<(DataFrame, ExtractionReport)> {
+        self.extractor.extract_with_cancellation(cancel).await
+    }
+
+    async fn mark_completed(&self, report: &ExtractionReport) -> Result<()> {
+        self.extractor.mark_completed(&report.dates).await
     }
 }
\ No newline at end of file
@@ -1,26 +1,111 @@
 // src/ticker_manager_pool.rs
 
+use crate::checkpoint::CheckpointStore;
+use crate::error::Result;
+use crate::extract_options::ExtractOptions;
 use crate::poly_agg_info::PolyAggInfo;
+use crate::report::PoolReport;
+use crate::session::PolygonHistorySession;
+use crate::sink::Sink;
 use crate::ticker_manager::TickerManager;
 use futures::future::join_all;
 use polars::prelude::*;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 
 pub struct TickerManagerPool {
     ticker_managers: Vec<TickerManager>,
+    session: Arc<PolygonHistorySession>,
 }
 
 impl TickerManagerPool {
-    pub fn new(poly_agg_infos: Vec<PolyAggInfo>) -> Self {
+    pub fn new(poly_agg_infos: Vec<PolyAggInfo>, session: Arc<PolygonHistorySession>) -> Self {
         let ticker_managers = poly_agg_infos
             .into_iter()
-            .map(TickerManager::new)
+            .map(|poly_agg_info| TickerManager::new(poly_agg_info, Arc::clone(&session)))
             .collect();
-        TickerManagerPool { ticker_managers }
+        TickerManagerPool { ticker_managers, session }
     }
 
-    pub async fn process_data_concurrently(&self) -> Result<Vec<DataFrame>, PolarsError> {
+    /// Extracts every ticker with the given concurrency, timeout and retry settings. Each ticker
+    /// gets its own `max_in_flight` budget and deadline.
+    pub fn with_options(mut self, options: ExtractOptions) -> Self {
+        self.ticker_managers = std::mem::take(&mut self.ticker_managers)
+            .into_iter()
+            .map(|manager| manager.with_options(options.clone()))
+            .collect();
+        self
+    }
+
+    /// Makes reruns resumable: every ticker only fetches the dates the store has not recorded
+    /// as completed, so an interrupted or extended backfill can simply be run again.
+    ///
+    /// Dates are recorded once the sink has written their bars; without a sink, call
+    /// `mark_completed` after keeping the returned bars.
+    pub fn with_checkpoints(mut self, checkpoints: CheckpointStore) -> Self {
+        self.ticker_managers = std::mem::take(&mut self.ticker_managers)
+            .into_iter()
+            .map(|manager| manager.with_checkpoints(checkpoints.clone()))
+            .collect();
+        self
+    }
+
+    /// Writes every ticker's processed bars to the sink as soon as the ticker is done.
+    pub fn with_sink(mut self, sink: Arc<dyn Sink>) -> Self {
+        self.ticker_managers = std::mem::take(&mut self.ticker_managers)
+            .into_iter()
+            .map(|manager| manager.with_sink(Arc::clone(&sink)))
+            .collect();
+        self
+    }
+
+    pub async fn process_data_concurrently(&self) -> Result<Vec<DataFrame>> {
         let futures = self.ticker_managers.iter().map(|manager| manager.process_data());
         let results = join_all(futures).await;
         results.into_iter().collect()
     }
+
+    /// Processes every ticker and aggregates their extraction reports.
+    ///
+    /// Dates that fail after all retries are listed in the report; only errors that retrying
+    /// cannot fix make the whole run fail.
+    pub async fn process_data_with_reports(&self) -> Result<(Vec<DataFrame>, PoolReport)> {
+        self.process_data_with_cancellation(CancellationToken::new()).await
+    }
+
+    /// Like `process_data_with_reports`, but stops every ticker once `cancel` is cancelled, e.g.
+    /// from a `tokio::signal::ctrl_c` handler.
+    ///
+    /// Returns the processed bars fetched so far; the dates still to fetch are listed by
+    /// `PoolReport::unfinished_dates`.
+    pub async fn process_data_with_cancellation(&self, cancel: CancellationToken) -> Result<(Vec<DataFrame>, PoolReport)> {
+        let earlier_rate_changes = self.session.rate_changes().len();
+        let futures = self
+            .ticker_managers
+            .iter()
+            .map(|manager| manager.process_data_with_cancellation(cancel.clone()));
+        let results = join_all(futures).await;
+
+        let mut dataframes = Vec::with_capacity(results.len());
+        let mut pool_report = PoolReport::default();
+        for result in results {
+            let (df, mut report) = result?;
+            dataframes.push(df);
+            // Rate changes are session-wide, so they are reported once for the pool
+            report.rate_changes.clear();
+            pool_report.reports.push(report);
+        }
+        pool_report.rate_changes = self.session.rate_changes_since(earlier_rate_changes);
+
+        Ok((dataframes, pool_report))
+    }
+
+    /// Records the completed dates of every ticker's report, for callers that keep the returned
+    /// bars themselves rather than through a sink.
+    pub async fn mark_completed(&self, pool_report: &PoolReport) -> Result<()> {
+        for (manager, report) in self.ticker_managers.iter().zip(&pool_report.reports) {
+            manager.mark_completed(report).await?;
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
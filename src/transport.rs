@@ -0,0 +1,175 @@
+// src/transport.rs
+
+use crate::config::SessionConfig;
+use crate::error::{Error, ErrorContext, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The raw outcome of an HTTP GET request.
+#[derive(Clone, Debug)]
+pub struct TransportResponse {
+    pub status: u16,
+    pub body: String,
+    /// The delay requested by a `Retry-After` header, if any.
+    pub retry_after: Option<Duration>,
+}
+
+impl TransportResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        TransportResponse { status, body: body.into(), retry_after: None }
+    }
+
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Sends GET requests on behalf of a `PolygonHistorySession`.
+///
+/// Implementations only move bytes; rate limiting and authentication are handled by the session.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn get(&self, url: &str) -> Result<TransportResponse>;
+}
+
+/// Sends requests over the network with a `reqwest::Client`.
+#[derive(Default)]
+pub struct ReqwestTransport {
+    client: Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: Client) -> Self {
+        ReqwestTransport { client }
+    }
+
+    /// Creates a transport whose client applies the config's connect and request timeouts.
+    pub fn from_config(config: &SessionConfig) -> Result<Self> {
+        let client = Client::builder()
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.request_timeout)
+            .build()
+            .map_err(|e| Error::Config { message: format!("Unable to build HTTP client: {}", e) })?;
+        Ok(ReqwestTransport { client })
+    }
+}
+
+#[async_trait]
+impl Transport for ReqwestTransport {
+    async fn get(&self, url: &str) -> Result<TransportResponse> {
+        let transport_error = |e: reqwest::Error| Error::Transport {
+            context: ErrorContext::new().url(strip_api_key(url)),
+            message: e.without_url().to_string(),
+        };
+        let response = self.client.get(url).send().await.map_err(transport_error)?;
+        let status = response.status().as_u16();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.map_err(transport_error)?;
+        Ok(TransportResponse { status, body, retry_after })
+    }
+}
+
+/// Serves canned responses from memory so the pipeline can run without a network.
+///
+/// Requests are matched on their URL with the `apiKey` parameter removed. When several responses
+/// are registered for one URL they are served in order, and the last one is repeated.
+#[derive(Default)]
+pub struct FixtureTransport {
+    responses: ResponseQueues,
+    requests: Mutex<Vec<String>>,
+}
+
+impl FixtureTransport {
+    pub fn new() -> Self {
+        FixtureTransport::default()
+    }
+
+    /// Registers the next response served for the given URL.
+    pub fn insert(&self, url: &str, response: TransportResponse) {
+        self.responses.push(url, response);
+    }
+
+    /// Registers the response served for the given URL, builder style.
+    pub fn with_response(self, url: &str, response: TransportResponse) -> Self {
+        self.insert(url, response);
+        self
+    }
+
+    /// Returns every URL requested so far, with the `apiKey` parameter removed.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Transport for FixtureTransport {
+    async fn get(&self, url: &str) -> Result<TransportResponse> {
+        let key = strip_api_key(url);
+        self.requests.lock().unwrap().push(key.clone());
+        self.responses.next(&key).ok_or_else(|| Error::Transport {
+            message: "No fixture registered for this URL".to_string(),
+            context: ErrorContext::new().url(key),
+        })
+    }
+}
+
+/// Canned responses keyed by URL without the `apiKey` parameter. Responses registered for one
+/// URL are served in order, and the last one is repeated.
+#[derive(Default)]
+pub(crate) struct ResponseQueues {
+    responses: Mutex<HashMap<String, VecDeque<TransportResponse>>>,
+}
+
+impl ResponseQueues {
+    pub(crate) fn push(&self, url: &str, response: TransportResponse) {
+        self.responses
+            .lock()
+            .unwrap()
+            .entry(strip_api_key(url))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Returns the next response for a URL whose `apiKey` parameter has already been removed.
+    pub(crate) fn next(&self, key: &str) -> Option<TransportResponse> {
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses.get_mut(key)?;
+        if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        }
+    }
+}
+
+/// Removes the `apiKey` query parameter from a URL.
+pub fn strip_api_key(url: &str) -> String {
+    let (path, query) = match url.split_once('?') {
+        Some((path, query)) => (path, query),
+        None => return url.to_string(),
+    };
+
+    let params: Vec<&str> = query
+        .split('&')
+        .filter(|param| !param.starts_with("apiKey="))
+        .collect();
+
+    if params.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, params.join("&"))
+    }
+}
@@ -0,0 +1,103 @@
+// src/update.rs
+
+use crate::calendar::{calendar_for_ticker, latest_closed_session};
+use crate::error::{Error, ErrorContext, Result};
+use crate::extract_options::ExtractOptions;
+use crate::poly_agg_info::PolyAggInfo;
+use crate::report::{ExtractionReport, PoolReport};
+use crate::session::PolygonHistorySession;
+use crate::store::BarStore;
+use crate::ticker_manager::TickerManager;
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::future::join_all;
+use polars::prelude::*;
+use std::sync::Arc;
+
+/// Brings a `BarStore` up to date with the latest closed session.
+///
+/// Each ticker is fetched from the trading day after its last stored `mkt_date`, processed like
+/// any other extraction, and appended to the store. Running an update twice is harmless: bars
+/// already stored are replaced rather than duplicated.
+pub struct Updater {
+    session: Arc<PolygonHistorySession>,
+    resolution: String,
+    multiplier: u32,
+    options: ExtractOptions,
+    first_date: Option<NaiveDate>,
+}
+
+impl Updater {
+    /// Creates an updater for a store holding bars of the given resolution.
+    pub fn new(session: Arc<PolygonHistorySession>, resolution: &str, multiplier: u32) -> Self {
+        Updater { session, resolution: resolution.to_string(), multiplier, options: ExtractOptions::default(), first_date: None }
+    }
+
+    /// Extracts with the given concurrency, timeout and retry settings.
+    pub fn with_options(mut self, options: ExtractOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Fetches tickers the store has no bars for from the given date. Without it, updating
+    /// such a ticker fails.
+    pub fn with_first_date(mut self, first_date: NaiveDate) -> Self {
+        self.first_date = Some(first_date);
+        self
+    }
+
+    /// Fetches every ticker's missing bars through the latest closed session and appends them.
+    ///
+    /// Dates that fail are listed in the returned report. Bars from the first failed date on
+    /// are not stored, so the next update fetches them again.
+    pub async fn update(&self, store: &dyn BarStore, tickers: &[&str]) -> Result<PoolReport> {
+        self.update_as_of(store, tickers, Utc::now()).await
+    }
+
+    /// Like `update`, but treats `now` as the current time when deciding which sessions closed.
+    pub async fn update_as_of(&self, store: &dyn BarStore, tickers: &[&str], now: DateTime<Utc>) -> Result<PoolReport> {
+        let futures = tickers.iter().map(|ticker| self.update_ticker(store, ticker, now));
+        let results = join_all(futures).await;
+
+        let mut pool_report = PoolReport::default();
+        for result in results {
+            pool_report.reports.push(result?);
+        }
+        Ok(pool_report)
+    }
+
+    async fn update_ticker(&self, store: &dyn BarStore, ticker: &str, now: DateTime<Utc>) -> Result<ExtractionReport> {
+        let start_date = match store.last_date(ticker).await? {
+            Some(last_date) => calendar_for_ticker(ticker).next_trading_day(last_date),
+            None => self.first_date.ok_or_else(|| Error::InvalidInput {
+                context: ErrorContext::new().ticker(ticker),
+                message: "No stored bars and no first date to update from".to_string(),
+            })?,
+        };
+        let end_date = latest_closed_session(ticker, now);
+        if start_date > end_date {
+            return Ok(ExtractionReport::new(ticker));
+        }
+
+        let poly_agg_info = PolyAggInfo {
+            ticker: ticker.to_string(),
+            start_date,
+            end_date,
+            resolution: self.resolution.clone(),
+            multiplier: self.multiplier,
+        };
+        let manager = TickerManager::new(poly_agg_info, Arc::clone(&self.session)).with_options(self.options.clone());
+        let (mut df, report) = manager.process_data_with_report().await?;
+
+        // Keep the store gap free: the next update resumes after the last stored date
+        if let Some(first_unfinished) = report.unfinished().map(|date| date.date[..10].to_string()).min() {
+            if df.height() > 0 {
+                df = df.lazy().filter(col("mkt_date").lt(lit(first_unfinished))).collect()?;
+            }
+        }
+        if df.height() > 0 {
+            store.append(&df).await?;
+        }
+
+        Ok(report)
+    }
+}
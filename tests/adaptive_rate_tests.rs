@@ -0,0 +1,110 @@
+// tests/adaptive_rate_tests.rs
+
+mod common;
+
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, test_config};
+use polyextract::{
+    AdaptiveRate, FixtureTransport, MinuteExtractor, PolygonHistorySession, RateChangeReason, SessionConfig,
+    TickerManagerPool, TransportResponse,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+const URL: &str = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/day/2024-01-02/2024-01-02";
+
+fn rate_limited() -> TransportResponse {
+    TransportResponse::new(429, "Too many requests")
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttling_halves_the_rate_once_per_cooldown() {
+    let transport = FixtureTransport::new().with_response(URL, rate_limited());
+    let session = PolygonHistorySession::with_transport(test_config(), Arc::new(transport))
+        .with_adaptive_rate(AdaptiveRate::new(10.0, 100.0).cooldown(Duration::from_secs(1)));
+
+    session.send_request(URL).await.unwrap();
+    session.send_request(URL).await.unwrap();
+    assert_eq!(session.rate_limiter().rate(), 50.0);
+
+    for _ in 0..3 {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        session.send_request(URL).await.unwrap();
+    }
+
+    // Bounded below by the minimum rate
+    let rates: Vec<_> = session.rate_changes().iter().map(|change| change.new_rate).collect();
+    assert_eq!(rates, vec![50.0, 25.0, 12.5, 10.0]);
+    assert!(session.rate_changes().iter().all(|change| change.reason == RateChangeReason::Throttled));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_successes_probe_up_to_the_maximum() {
+    let transport = FixtureTransport::new().with_response(URL, TransportResponse::new(200, "{}"));
+    let config = SessionConfig::builder().api_key("test-key").requests_per_second(20).build().unwrap();
+    let session = PolygonHistorySession::with_transport(config, Arc::new(transport))
+        .with_adaptive_rate(AdaptiveRate::new(1.0, 25.0).increase(2.0).successes_before_increase(5));
+
+    for _ in 0..20 {
+        session.send_request(URL).await.unwrap();
+    }
+
+    let rates: Vec<_> = session.rate_changes().iter().map(|change| change.new_rate.round()).collect();
+    assert_eq!(rates, vec![22.0, 24.0, 25.0]);
+    assert!(session.rate_changes().iter().all(|change| change.reason == RateChangeReason::Probe));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retry_after_pauses_every_request() {
+    let transport = FixtureTransport::new()
+        .with_response(URL, rate_limited().with_retry_after(Duration::from_secs(30)))
+        .with_response(URL, TransportResponse::new(200, "{}"));
+    let session = PolygonHistorySession::with_transport(test_config(), Arc::new(transport))
+        .with_adaptive_rate(AdaptiveRate::default());
+
+    session.send_request(URL).await.unwrap();
+    assert!(session.rate_limiter().wait_time().unwrap() >= Duration::from_secs(29));
+
+    let started = tokio::time::Instant::now();
+    session.send_request(URL).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(29));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_extraction_report_lists_rate_changes() {
+    let transport = FixtureTransport::new()
+        .with_response(&aggs_url("AAPL", "2024-01-02"), rate_limited())
+        .with_response(&aggs_url("AAPL", "2024-01-02"), page("AAPL", bars(market_open("2024-01-02"), 391), None));
+    let session = Arc::new(
+        PolygonHistorySession::with_transport(test_config(), Arc::new(transport))
+            .with_adaptive_rate(AdaptiveRate::new(10.0, 100.0)),
+    );
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), session);
+
+    let (df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(df.height(), 391);
+    assert_eq!(report.rate_changes.len(), 1);
+    assert_eq!(report.rate_changes[0].previous_rate, 100.0);
+    assert_eq!(report.rate_changes[0].new_rate, 50.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pool_report_lists_rate_changes_once() {
+    let transport = FixtureTransport::new()
+        .with_response(&aggs_url("AAPL", "2024-01-02"), rate_limited())
+        .with_response(&aggs_url("AAPL", "2024-01-02"), page("AAPL", bars(market_open("2024-01-02"), 391), None))
+        .with_response(&aggs_url("MSFT", "2024-01-02"), page("MSFT", bars(market_open("2024-01-02"), 391), None));
+    let session = Arc::new(
+        PolygonHistorySession::with_transport(test_config(), Arc::new(transport))
+            .with_adaptive_rate(AdaptiveRate::new(10.0, 100.0)),
+    );
+    let poly_agg_infos = ["AAPL", "MSFT"]
+        .iter()
+        .map(|ticker| poly_agg_info(ticker, date(2024, 1, 2), date(2024, 1, 2)))
+        .collect();
+
+    let (_, pool_report) = TickerManagerPool::new(poly_agg_infos, session).process_data_with_reports().await.unwrap();
+
+    assert_eq!(pool_report.rate_changes.len(), 1);
+    assert!(pool_report.reports.iter().all(|report| report.rate_changes.is_empty()));
+}
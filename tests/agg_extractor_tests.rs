@@ -0,0 +1,169 @@
+// tests/agg_extractor_tests.rs
+
+mod common;
+
+use chrono::NaiveDate;
+use common::{bars, market_open, page, test_config};
+use polyextract::{
+    AggExtractor, DateStatus, Error, FixtureTransport, PolyAggInfo, PolygonHistorySession, SessionConfig, Timespan,
+    TickerManager,
+};
+use std::sync::Arc;
+
+#[test]
+fn test_timespan_parsing() {
+    assert_eq!("minute".parse::<Timespan>().unwrap(), Timespan::Minute);
+    assert_eq!("Day".parse::<Timespan>().unwrap(), Timespan::Day);
+    assert_eq!("quarter".parse::<Timespan>().unwrap(), Timespan::Quarter);
+    assert!("fortnight".parse::<Timespan>().is_err());
+}
+
+#[tokio::test]
+async fn test_agg_extractor_five_minute_bars() {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let poly_agg_info = PolyAggInfo {
+        ticker: "AAPL".to_string(),
+        start_date: date,
+        end_date: date,
+        resolution: "minute".to_string(),
+        multiplier: 5,
+    };
+    // A full extended-hours session, 04:00 to 20:00 ET, of 5 minute bars
+    let pre_market = market_open("2024-01-02") - 330 * 60_000;
+    let five_minute_bars = (0..192).flat_map(|i| bars(pre_market + i * 5 * 60_000, 1)).collect();
+    let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/5/minute/2024-01-02/2024-01-02?adjusted=true&sort=asc&limit=50000";
+    let transport = Arc::new(FixtureTransport::new().with_response(url, page("AAPL", five_minute_bars, None)));
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+
+    let agg_extractor = AggExtractor::new(poly_agg_info, session);
+    let df = agg_extractor.extractor.extract().await.unwrap();
+
+    assert_eq!(transport.requests().len(), 1);
+    assert_eq!(df.height(), 192);
+}
+
+#[tokio::test]
+async fn test_ticker_manager_unsupported_resolution() {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let poly_agg_info = PolyAggInfo {
+        ticker: "AAPL".to_string(),
+        start_date: date,
+        end_date: date,
+        resolution: "fortnight".to_string(),
+        multiplier: 1,
+    };
+
+    let config = SessionConfig::builder().api_key("test-key").build().unwrap();
+    let session = Arc::new(PolygonHistorySession::with_transport(config, Arc::new(FixtureTransport::new())));
+
+    let ticker_manager = TickerManager::new(poly_agg_info, session);
+    match ticker_manager.process_data().await {
+        Err(Error::InvalidInput { context, .. }) => assert_eq!(context.ticker.as_deref(), Some("AAPL")),
+        other => panic!("Expected an invalid input error, got {:?}", other.map(|df| df.height())),
+    }
+}
+
+#[tokio::test]
+async fn test_agg_extractor_follows_next_url() {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let poly_agg_info = PolyAggInfo {
+        ticker: "AAPL".to_string(),
+        start_date: date,
+        end_date: date,
+        resolution: "second".to_string(),
+        multiplier: 1,
+    };
+    let open = market_open("2024-01-02");
+    let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/second/2024-01-02/2024-01-02?adjusted=true&sort=asc&limit=5000";
+    let cursor_url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/second/2024-01-02/2024-01-02?cursor=page2";
+    let first_page = (0..5000).flat_map(|i| bars(open + i * 1000, 1)).collect();
+    let second_page = (5000..7500).flat_map(|i| bars(open + i * 1000, 1)).collect();
+    let transport = Arc::new(
+        FixtureTransport::new()
+            .with_response(url, page("AAPL", first_page, Some(cursor_url)))
+            .with_response(cursor_url, page("AAPL", second_page, None)),
+    );
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+
+    let mut agg_extractor = AggExtractor::new(poly_agg_info, session);
+    agg_extractor.extractor.limit = 5000;
+    let df = agg_extractor.extractor.extract().await.unwrap();
+
+    // The bars of both pages are merged
+    assert_eq!(transport.requests().len(), 2);
+    assert_eq!(df.height(), 7500);
+}
+
+#[tokio::test]
+async fn test_daily_bars_use_a_single_window() {
+    let poly_agg_info = PolyAggInfo {
+        ticker: "AAPL".to_string(),
+        start_date: common::date(2023, 12, 25),
+        end_date: common::date(2024, 1, 7),
+        resolution: "day".to_string(),
+        multiplier: 1,
+    };
+    let trading_days = [
+        "2023-12-26", "2023-12-27", "2023-12-28", "2023-12-29", "2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05",
+    ];
+    // Polygon stamps daily bars at midnight in the market timezone
+    let daily_bars = trading_days
+        .iter()
+        .flat_map(|day| bars(market_open(day) - 570 * 60_000, 1))
+        .collect();
+    let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/day/2023-12-26/2024-01-05?adjusted=true&sort=asc&limit=50000";
+    let transport = Arc::new(FixtureTransport::new().with_response(url, page("AAPL", daily_bars, None)));
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+
+    let (df, report) = AggExtractor::new(poly_agg_info, session).extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(transport.requests().len(), 1);
+    assert_eq!(report.dates.len(), 1);
+    assert_eq!(report.dates[0].date, "2023-12-26..2024-01-05");
+    assert_eq!(report.dates[0].bars, 8);
+    let mkt_dates: Vec<_> = df.column("mkt_date").unwrap().str().unwrap().into_no_null_iter().collect();
+    assert_eq!(mkt_dates, trading_days);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_hourly_bars_are_split_into_windows_that_fit_the_limit() {
+    let poly_agg_info = PolyAggInfo {
+        ticker: "AAPL".to_string(),
+        start_date: common::date(2015, 1, 1),
+        end_date: common::date(2024, 12, 31),
+        resolution: "hour".to_string(),
+        multiplier: 1,
+    };
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(FixtureTransport::new())));
+
+    let (_, report) = AggExtractor::new(poly_agg_info, session).extractor.extract_with_report().await.unwrap();
+
+    // 50,000 hourly bars cover a little over 2,083 days, so ten years take two windows
+    assert_eq!(report.dates.len(), 2);
+    assert!(report.dates[0].date.starts_with("2015-01-02.."));
+    assert!(report.dates[1].date.ends_with("..2024-12-31"));
+    assert!(report.dates.iter().all(|date| date.status == DateStatus::Failed));
+}
+
+#[tokio::test]
+async fn test_ticker_manager_keeps_daily_bars() {
+    let poly_agg_info = PolyAggInfo {
+        ticker: "AAPL".to_string(),
+        start_date: common::date(2024, 1, 2),
+        end_date: common::date(2024, 1, 5),
+        resolution: "day".to_string(),
+        multiplier: 1,
+    };
+    // Daily bars are stamped at midnight, outside regular market hours
+    let daily_bars = ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"]
+        .iter()
+        .flat_map(|day| bars(market_open(day) - 570 * 60_000, 1))
+        .collect();
+    let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/day/2024-01-02/2024-01-05?adjusted=true&sort=asc&limit=50000";
+    let transport = Arc::new(FixtureTransport::new().with_response(url, page("AAPL", daily_bars, None)));
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport));
+
+    let df = TickerManager::new(poly_agg_info, session).process_data().await.unwrap();
+
+    assert_eq!(df.height(), 4);
+}
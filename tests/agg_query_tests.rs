@@ -0,0 +1,53 @@
+// tests/agg_query_tests.rs
+
+mod common;
+
+use common::{date, test_config};
+use polyextract::{AggExtractor, AggQuery, Error, FixtureTransport, PolyAggInfo, PolygonHistorySession, SortOrder, Timespan};
+use std::sync::Arc;
+
+#[test]
+fn test_agg_query_renders_url() {
+    let query = AggQuery::new("AAPL", 5, Timespan::Minute, date(2024, 1, 2), date(2024, 1, 2));
+    assert_eq!(query.to_url(), "/v2/aggs/ticker/AAPL/range/5/minute/2024-01-02/2024-01-02?adjusted=true&sort=asc&limit=50000");
+    assert_eq!(query.date_label(), "2024-01-02");
+
+    let query = AggQuery::new("AAPL", 1, Timespan::Day, date(2007, 1, 1), date(2024, 1, 1))
+        .adjusted(false)
+        .sort(SortOrder::Desc)
+        .limit(120);
+    assert_eq!(query.to_url(), "/v2/aggs/ticker/AAPL/range/1/day/2007-01-01/2024-01-01?adjusted=false&sort=desc&limit=120");
+    assert_eq!(query.date_label(), "2007-01-01..2024-01-01");
+}
+
+#[test]
+fn test_agg_query_percent_encodes_ticker() {
+    let crypto = AggQuery::new("X:BTCUSD", 1, Timespan::Hour, date(2024, 1, 1), date(2024, 1, 1));
+    assert!(crypto.to_url().starts_with("/v2/aggs/ticker/X%3ABTCUSD/range/1/hour/2024-01-01/2024-01-01?"));
+
+    let option = AggQuery::new("O:SPY251219C00650000", 1, Timespan::Day, date(2024, 1, 2), date(2024, 1, 2));
+    assert!(option.to_url().starts_with("/v2/aggs/ticker/O%3ASPY251219C00650000/range/1/day/"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_crypto_query_keeps_its_date() {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(FixtureTransport::new())));
+    let poly_agg_info = PolyAggInfo {
+        ticker: "X:BTCUSD".to_string(),
+        start_date: date(2024, 1, 1),
+        end_date: date(2024, 1, 1),
+        resolution: "minute".to_string(),
+        multiplier: 1,
+    };
+
+    match AggExtractor::new(poly_agg_info, session).extractor.extract().await {
+        Err(Error::RetriesExhausted { context, .. }) => {
+            assert_eq!(context.date.as_deref(), Some("2024-01-01"));
+            assert_eq!(
+                context.url.as_deref(),
+                Some("/v2/aggs/ticker/X%3ABTCUSD/range/1/minute/2024-01-01/2024-01-01?adjusted=true&sort=asc&limit=50000")
+            );
+        }
+        other => panic!("Expected exhausted retries, got {:?}", other.map(|df| df.height())),
+    }
+}
@@ -0,0 +1,127 @@
+// tests/cache_tests.rs
+
+mod common;
+
+use chrono::Utc;
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, temp_dir, test_config};
+use polyextract::{
+    AggExtractor, AggQuery, CacheMode, Error, FixtureTransport, MarketTimezone, PolygonHistorySession,
+    ResponseCache, TickerManagerPool, Timespan,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn fixture_transport(tickers: &[&str]) -> Arc<FixtureTransport> {
+    let transport = FixtureTransport::new();
+    for ticker in tickers {
+        transport.insert(&aggs_url(ticker, "2024-01-02"), page(ticker, bars(market_open("2024-01-02"), 391), None));
+    }
+    Arc::new(transport)
+}
+
+fn session(transport: Arc<FixtureTransport>, cache: ResponseCache) -> Arc<PolygonHistorySession> {
+    Arc::new(PolygonHistorySession::with_transport(test_config(), transport).with_cache(cache))
+}
+
+#[tokio::test]
+async fn test_pool_run_replays_offline() {
+    let dir = temp_dir("replay");
+    let tickers = ["AAPL", "X:BTCUSD"];
+    let poly_agg_infos: Vec<_> = tickers
+        .iter()
+        .map(|ticker| poly_agg_info(ticker, date(2024, 1, 2), date(2024, 1, 2)))
+        .collect();
+
+    let online = fixture_transport(&tickers);
+    let recorded = TickerManagerPool::new(poly_agg_infos.clone(), session(online.clone(), ResponseCache::new(&dir)))
+        .process_data_concurrently()
+        .await
+        .unwrap();
+    assert_eq!(online.requests().len(), 2);
+
+    let offline = Arc::new(FixtureTransport::new());
+    let offline_cache = ResponseCache::new(&dir).with_mode(CacheMode::Offline);
+    let replayed = TickerManagerPool::new(poly_agg_infos, session(offline.clone(), offline_cache))
+        .process_data_concurrently()
+        .await
+        .unwrap();
+
+    assert!(offline.requests().is_empty());
+    assert_eq!(recorded, replayed);
+    let query = AggQuery::new("AAPL", 1, Timespan::Minute, date(2024, 1, 2), date(2024, 1, 2));
+    let entry = std::fs::read_to_string(ResponseCache::new(&dir).path(&query)).unwrap();
+    assert!(!entry.contains("test-key"));
+}
+
+#[tokio::test]
+async fn test_offline_miss_fails_without_requests() {
+    let transport = fixture_transport(&["AAPL"]);
+    let cache = ResponseCache::new(temp_dir("offline-miss")).with_mode(CacheMode::Offline);
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2));
+
+    let result = AggExtractor::new(poly_agg_info, session(transport.clone(), cache)).extractor.extract().await;
+
+    assert!(matches!(result, Err(Error::Cache { .. })));
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_read_only_and_bypass_do_not_write() {
+    let dir = temp_dir("read-only");
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2));
+
+    for mode in [CacheMode::ReadOnly, CacheMode::Bypass] {
+        let transport = fixture_transport(&["AAPL"]);
+        let cache = ResponseCache::new(&dir).with_mode(mode);
+        AggExtractor::new(poly_agg_info.clone(), session(transport.clone(), cache)).extractor.extract().await.unwrap();
+        assert_eq!(transport.requests().len(), 1);
+    }
+    assert!(!dir.exists());
+}
+
+#[tokio::test]
+async fn test_concurrent_stores_of_an_entry_all_succeed() {
+    let dir = temp_dir("concurrent");
+    let cache = Arc::new(ResponseCache::new(&dir));
+    let query = AggQuery::new("AAPL", 1, Timespan::Minute, date(2024, 1, 2), date(2024, 1, 2));
+
+    let tasks: Vec<_> = (0..20)
+        .map(|i| {
+            let (cache, query) = (Arc::clone(&cache), query.clone());
+            tokio::spawn(async move { cache.store(&query, &[format!("page {}", i)]).await })
+        })
+        .collect();
+    for task in futures::future::join_all(tasks).await {
+        task.unwrap().unwrap();
+    }
+
+    // One writer wins and no temporary files are left behind
+    let pages = cache.load(&query).await.unwrap().unwrap();
+    assert!(pages[0].starts_with("page "));
+    let entry_dir = cache.path(&query).parent().unwrap().to_path_buf();
+    assert_eq!(std::fs::read_dir(entry_dir).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_only_open_days_expire() {
+    let dir = temp_dir("ttl");
+    let today = Utc::now().with_timezone(&MarketTimezone::Eastern.timezone()).date_naive();
+    let today_url = aggs_url("X:BTCUSD", &today.format("%Y-%m-%d").to_string());
+
+    let transport = fixture_transport(&["X:BTCUSD"]);
+    transport.insert(&today_url, page("X:BTCUSD", Vec::new(), None));
+    let cache = ResponseCache::new(&dir).with_open_ttl(Duration::ZERO);
+    let session = session(transport.clone(), cache);
+
+    for _ in 0..2 {
+        for day in [date(2024, 1, 2), today] {
+            let extractor = AggExtractor::new(poly_agg_info("X:BTCUSD", day, day), Arc::clone(&session));
+            extractor.extractor.extract().await.unwrap();
+        }
+    }
+
+    // The closed day is fetched once; today's entry expires immediately and is fetched again
+    let requests = transport.requests();
+    assert_eq!(requests.iter().filter(|url| url.contains("/2024-01-02/")).count(), 1);
+    assert_eq!(requests.iter().filter(|url| url.starts_with(&today_url)).count(), 2);
+}
@@ -0,0 +1,86 @@
+// tests/calendar_tests.rs
+
+use chrono::{NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use polyextract::calendar::calendar_for_ticker;
+use polyextract::{latest_closed_session, EveryDayCalendar, MarketTimezone, NyseCalendar, TradingCalendar};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn test_nyse_holidays() {
+    let holidays = [
+        date(2024, 1, 1),   // New Year's Day
+        date(2024, 1, 15),  // Martin Luther King Jr. Day
+        date(2024, 2, 19),  // Presidents' Day
+        date(2024, 3, 29),  // Good Friday
+        date(2024, 5, 27),  // Memorial Day
+        date(2024, 6, 19),  // Juneteenth
+        date(2024, 7, 4),   // Independence Day
+        date(2024, 9, 2),   // Labor Day
+        date(2024, 11, 28), // Thanksgiving
+        date(2024, 12, 25), // Christmas
+        date(2022, 12, 26), // Christmas observed on Monday
+        date(2021, 7, 5),   // Independence Day observed on Monday
+        date(2025, 1, 9),   // National day of mourning
+    ];
+
+    for holiday in holidays {
+        assert!(!NyseCalendar.is_trading_day(holiday), "{} should be a holiday", holiday);
+    }
+}
+
+#[test]
+fn test_nyse_trading_days() {
+    // New Year's Day 2022 fell on a Saturday and was not observed on the Friday before
+    assert!(NyseCalendar.is_trading_day(date(2021, 12, 31)));
+    // Juneteenth was not an exchange holiday before 2022
+    assert!(NyseCalendar.is_trading_day(date(2021, 6, 18)));
+    assert!(!NyseCalendar.is_trading_day(date(2024, 1, 6)));
+
+    let days = NyseCalendar.trading_days(date(2023, 12, 29), date(2024, 1, 3));
+    assert_eq!(days, vec![date(2023, 12, 29), date(2024, 1, 2), date(2024, 1, 3)]);
+    assert_eq!(NyseCalendar.next_trading_day(date(2024, 3, 28)), date(2024, 4, 1));
+}
+
+#[test]
+fn test_nyse_early_closes() {
+    let one_pm = NaiveTime::from_hms_opt(13, 0, 0);
+    assert_eq!(NyseCalendar.early_close(date(2024, 7, 3)), one_pm);
+    assert_eq!(NyseCalendar.early_close(date(2024, 11, 29)), one_pm);
+    assert_eq!(NyseCalendar.early_close(date(2024, 12, 24)), one_pm);
+    assert_eq!(NyseCalendar.early_close(date(2024, 12, 23)), None);
+
+    let (open, close) = MarketTimezone::Eastern.market_hours_on_date("2024-11-29").unwrap();
+    assert_eq!(open.hour(), 9);
+    assert_eq!(close.hour(), 13);
+}
+
+#[test]
+fn test_calendar_for_ticker() {
+    let new_years_day = date(2024, 1, 1);
+    assert!(!calendar_for_ticker("AAPL").is_trading_day(new_years_day));
+    assert!(calendar_for_ticker("X:BTCUSD").is_trading_day(new_years_day));
+    assert!(calendar_for_ticker("C:EURUSD").is_trading_day(new_years_day));
+    assert_eq!(EveryDayCalendar.trading_days(date(2024, 1, 1), date(2024, 1, 7)).len(), 7);
+}
+
+#[test]
+fn test_latest_closed_session() {
+    let at = |day, hour, minute| Utc.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap();
+
+    // The session closes at 16:00 Eastern, 21:00 UTC in winter
+    assert_eq!(latest_closed_session("AAPL", at(5, 20, 59)), date(2024, 1, 4));
+    assert_eq!(latest_closed_session("AAPL", at(5, 21, 0)), date(2024, 1, 5));
+    assert_eq!(latest_closed_session("AAPL", at(7, 12, 0)), date(2024, 1, 5));
+    assert_eq!(latest_closed_session("AAPL", at(2, 12, 0)), date(2023, 12, 29));
+
+    // Early closes count from 13:00 Eastern
+    let black_friday = Utc.with_ymd_and_hms(2023, 11, 24, 18, 30, 0).unwrap();
+    assert_eq!(latest_closed_session("AAPL", black_friday), date(2023, 11, 24));
+
+    // Crypto sessions close at midnight Eastern
+    assert_eq!(latest_closed_session("X:BTCUSD", at(6, 3, 0)), date(2024, 1, 4));
+    assert_eq!(latest_closed_session("X:BTCUSD", at(6, 6, 0)), date(2024, 1, 5));
+}
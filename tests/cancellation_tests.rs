@@ -0,0 +1,91 @@
+// tests/cancellation_tests.rs
+
+mod common;
+
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, test_config, SlowTransport};
+use polyextract::{
+    CancellationToken, DateStatus, ExtractOptions, MinuteExtractor, PolygonHistorySession, TickerManagerPool,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DATES: [&str; 4] = ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"];
+
+/// A session that answers each request after a second.
+fn slow_session(tickers: &[&str]) -> Arc<PolygonHistorySession> {
+    let transport = SlowTransport::default();
+    for ticker in tickers {
+        for date in DATES {
+            transport.fixtures.insert(&aggs_url(ticker, date), page(ticker, bars(market_open(date), 10), None));
+        }
+    }
+    Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)))
+}
+
+/// Cancels the token after the given time.
+fn cancel_after(delay: Duration) -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        token.cancel();
+    });
+    cancel
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelled_extraction_returns_partial_data() {
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5));
+    let mut extractor = MinuteExtractor::new(poly_agg_info, slow_session(&["AAPL"]));
+    extractor.extractor.options = ExtractOptions::default().max_in_flight(1);
+
+    let (df, report) = extractor
+        .extractor
+        .extract_with_cancellation(cancel_after(Duration::from_millis(2500)))
+        .await
+        .unwrap();
+
+    // Two dates finished, the third was in flight and the fourth never sent
+    assert_eq!(df.height(), 20);
+    let statuses: Vec<_> = report.dates.iter().map(|date| date.status).collect();
+    assert_eq!(
+        statuses,
+        vec![DateStatus::Succeeded, DateStatus::Succeeded, DateStatus::Cancelled, DateStatus::Cancelled]
+    );
+    let unfinished: Vec<_> = report.unfinished().map(|date| date.date.as_str()).collect();
+    assert_eq!(unfinished, vec!["2024-01-04", "2024-01-05"]);
+    assert!(!report.is_complete());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelled_pool_reports_unfinished_dates() {
+    let tickers = ["AAPL", "MSFT"];
+    let poly_agg_infos = tickers
+        .iter()
+        .map(|ticker| poly_agg_info(ticker, date(2024, 1, 2), date(2024, 1, 5)))
+        .collect();
+    let pool = TickerManagerPool::new(poly_agg_infos, slow_session(&tickers))
+        .with_options(ExtractOptions::default().max_in_flight(2));
+
+    let (dataframes, pool_report) = pool
+        .process_data_with_cancellation(cancel_after(Duration::from_millis(1500)))
+        .await
+        .unwrap();
+
+    assert_eq!(dataframes.len(), 2);
+    assert!(dataframes.iter().all(|df| df.height() == 20));
+    assert_eq!(pool_report.succeeded(), 4);
+    assert_eq!(pool_report.cancelled(), 4);
+    assert_eq!(pool_report.unfinished_dates().len(), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_uncancelled_extraction_finishes() {
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5));
+    let extractor = MinuteExtractor::new(poly_agg_info, slow_session(&["AAPL"]));
+
+    let (df, report) = extractor.extractor.extract_with_cancellation(CancellationToken::new()).await.unwrap();
+
+    assert_eq!(df.height(), 40);
+    assert!(report.is_complete());
+}
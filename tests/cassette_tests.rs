@@ -0,0 +1,65 @@
+// tests/cassette_tests.rs
+
+mod common;
+
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, test_config};
+use polyextract::{
+    Error, FixtureTransport, MinuteExtractor, PolygonHistorySession, RecordingTransport, ReplayTransport,
+    TransportResponse,
+};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_recorded_session_replays_identically() {
+    let path = std::env::temp_dir().join(format!("polyextract-cassette-{}.json", std::process::id()));
+    let fixtures = FixtureTransport::new()
+        .with_response(&aggs_url("AAPL", "2024-01-02"), TransportResponse::new(502, "Bad Gateway"))
+        .with_response(&aggs_url("AAPL", "2024-01-02"), page("AAPL", bars(market_open("2024-01-02"), 391), None))
+        .with_response(&aggs_url("AAPL", "2024-01-03"), page("AAPL", bars(market_open("2024-01-03"), 391), None));
+    let recorder = Arc::new(RecordingTransport::new(Arc::new(fixtures), &path));
+    let recording = Arc::new(PolygonHistorySession::with_transport(test_config(), recorder.clone()));
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3));
+
+    let recorded = MinuteExtractor::new(poly_agg_info.clone(), recording).extractor.extract().await.unwrap();
+    recorder.save().unwrap();
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("test-key"));
+    assert!(cassette.contains("Bad Gateway"));
+
+    let replay = Arc::new(ReplayTransport::from_file(&path).unwrap());
+    let replaying = Arc::new(PolygonHistorySession::with_transport(test_config(), replay));
+    let replayed = MinuteExtractor::new(poly_agg_info, replaying).extractor.extract().await.unwrap();
+
+    assert_eq!(recorded.height(), 782);
+    assert_eq!(recorded, replayed);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay_fails_fast_on_unmatched_requests() {
+    let path = std::env::temp_dir().join(format!("polyextract-empty-cassette-{}.json", std::process::id()));
+    RecordingTransport::new(Arc::new(FixtureTransport::new()), &path).save().unwrap();
+
+    let replay = Arc::new(ReplayTransport::from_file(&path).unwrap());
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), replay));
+    let result = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), session)
+        .extractor
+        .extract()
+        .await;
+
+    match result {
+        Err(Error::Cassette { context, .. }) => {
+            assert_eq!(context.date.as_deref(), Some("2024-01-02"));
+            assert!(context.url.unwrap().contains("/AAPL/range/1/minute/2024-01-02/2024-01-02"));
+        }
+        other => panic!("Expected a cassette error, got {:?}", other.map(|df| df.height())),
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_missing_cassette_is_an_error() {
+    let result = ReplayTransport::from_file("tests/cassettes/does_not_exist.json");
+    assert!(matches!(result, Err(Error::Cassette { .. })));
+}
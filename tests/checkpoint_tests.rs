@@ -0,0 +1,165 @@
+// tests/checkpoint_tests.rs
+
+mod common;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, temp_dir, test_config};
+use polars::prelude::DataFrame;
+use polyextract::{
+    AggExtractor, AggQuery, CheckpointKey, CheckpointStore, Error, ErrorContext, ExtractOptions, FixtureTransport,
+    MarketTimezone, MemoryBarStore, PolyAggInfo, PolygonHistorySession, RetryPolicy, Sink, TickerManager,
+    TickerManagerPool, Timespan,
+};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+const DATES: [&str; 4] = ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"];
+
+/// Serves every date except the skipped ones.
+fn fixture_transport(skipped: &[&str]) -> Arc<FixtureTransport> {
+    let transport = FixtureTransport::new();
+    for date in DATES.iter().filter(|date| !skipped.contains(date)) {
+        transport.insert(&aggs_url("AAPL", date), page("AAPL", bars(market_open(date), 10), None));
+    }
+    Arc::new(transport)
+}
+
+fn pool(transport: Arc<FixtureTransport>, checkpoints: &CheckpointStore) -> TickerManagerPool {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport));
+    TickerManagerPool::new(vec![poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5))], session)
+        .with_options(ExtractOptions::default().retry_policy(RetryPolicy::none()))
+        .with_checkpoints(checkpoints.clone())
+}
+
+fn ticker_manager(transport: Arc<FixtureTransport>, checkpoints: &CheckpointStore, sink: Arc<dyn Sink>) -> TickerManager {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport));
+    TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5)), session)
+        .with_options(ExtractOptions::default().retry_policy(RetryPolicy::none()))
+        .with_checkpoints(checkpoints.clone())
+        .with_sink(sink)
+}
+
+/// A sink whose writes always fail.
+struct FailingSink;
+
+#[async_trait]
+impl Sink for FailingSink {
+    async fn write(&self, _bars: &DataFrame) -> polyextract::Result<()> {
+        Err(Error::Store { context: ErrorContext::new(), message: "disk full".to_string() })
+    }
+}
+
+#[tokio::test]
+async fn test_manifest_records_closed_days_only() {
+    let checkpoints = CheckpointStore::new(temp_dir("checkpoint-manifest"));
+    let key = CheckpointKey::new("X:BTCUSD", 1, Timespan::Minute, true);
+    let today = Utc::now().with_timezone(&MarketTimezone::Eastern.timezone()).date_naive();
+
+    checkpoints.mark_completed(&key, [date(2024, 1, 3), date(2024, 1, 2), today]).await.unwrap();
+    checkpoints.mark_completed(&key, [date(2024, 1, 2)]).await.unwrap();
+
+    assert_eq!(checkpoints.completed(&key).await.unwrap(), BTreeSet::from([date(2024, 1, 2), date(2024, 1, 3)]));
+    assert!(checkpoints.path(&key).ends_with("X%3ABTCUSD/1_minute_adjusted=true.json"));
+
+    checkpoints.clear(&key).await.unwrap();
+    assert!(checkpoints.completed(&key).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_rerun_only_fetches_missing_dates() {
+    let checkpoints = CheckpointStore::new(temp_dir("checkpoint-rerun"));
+
+    let store = Arc::new(MemoryBarStore::new());
+
+    // The first run fails on one date
+    let (dataframes, report) = pool(fixture_transport(&["2024-01-04"]), &checkpoints)
+        .with_sink(store.clone())
+        .process_data_with_reports()
+        .await
+        .unwrap();
+    assert_eq!(dataframes[0].height(), 30);
+    assert_eq!(report.failed(), 1);
+
+    // The rerun only asks for that date
+    let transport = fixture_transport(&[]);
+    let (dataframes, report) =
+        pool(transport.clone(), &checkpoints).with_sink(store.clone()).process_data_with_reports().await.unwrap();
+    assert_eq!(dataframes[0].height(), 10);
+    assert_eq!(store.bars().height(), 40);
+    assert!(report.is_complete());
+    assert_eq!(transport.requests(), vec![aggs_url("AAPL", "2024-01-04")]);
+
+    // Once everything is stored, another run sends nothing
+    let transport = fixture_transport(&[]);
+    let dataframes = pool(transport.clone(), &checkpoints).with_sink(store).process_data_concurrently().await.unwrap();
+    assert_eq!(dataframes[0].height(), 0);
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_run_marks_nothing() {
+    let checkpoints = CheckpointStore::new(temp_dir("checkpoint-failed-run"));
+    let store = Arc::new(MemoryBarStore::new());
+
+    // One date fails, so the run returns an error and keeps nothing
+    let result = ticker_manager(fixture_transport(&["2024-01-04"]), &checkpoints, store.clone()).process_data().await;
+    assert!(result.is_err());
+
+    // The rerun fetches every date again
+    let transport = fixture_transport(&[]);
+    let df = ticker_manager(transport.clone(), &checkpoints, store.clone()).process_data().await.unwrap();
+    assert_eq!(df.height(), 40);
+    assert_eq!(transport.requests().len(), 4);
+    assert_eq!(store.bars().height(), 40);
+}
+
+#[tokio::test]
+async fn test_failed_sink_write_marks_nothing() {
+    let checkpoints = CheckpointStore::new(temp_dir("checkpoint-failed-sink"));
+
+    let result = ticker_manager(fixture_transport(&[]), &checkpoints, Arc::new(FailingSink)).process_data().await;
+    assert!(matches!(result, Err(Error::Store { .. })));
+
+    let transport = fixture_transport(&[]);
+    ticker_manager(transport.clone(), &checkpoints, Arc::new(MemoryBarStore::new())).process_data().await.unwrap();
+    assert_eq!(transport.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn test_without_a_sink_dates_are_marked_by_the_caller() {
+    let checkpoints = CheckpointStore::new(temp_dir("checkpoint-no-sink"));
+
+    let run = pool(fixture_transport(&[]), &checkpoints);
+    let (_, report) = run.process_data_with_reports().await.unwrap();
+
+    // Nothing is recorded until the caller says the bars are kept
+    let transport = fixture_transport(&[]);
+    pool(transport.clone(), &checkpoints).process_data_with_reports().await.unwrap();
+    assert_eq!(transport.requests().len(), 4);
+
+    run.mark_completed(&report).await.unwrap();
+    let transport = fixture_transport(&[]);
+    pool(transport.clone(), &checkpoints).process_data_with_reports().await.unwrap();
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_windows_do_not_span_completed_days() {
+    let checkpoints = CheckpointStore::new(temp_dir("checkpoint-windows"));
+    let key = CheckpointKey::new("AAPL", 1, Timespan::Day, true);
+    checkpoints.mark_completed(&key, [date(2024, 1, 3)]).await.unwrap();
+
+    let transport = Arc::new(FixtureTransport::new());
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+    let poly_agg_info = PolyAggInfo { resolution: "day".to_string(), ..poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5)) };
+    let mut extractor = AggExtractor::new(poly_agg_info, session);
+    extractor.extractor.options = ExtractOptions::default().retry_policy(RetryPolicy::none());
+    extractor.extractor.checkpoints = Some(checkpoints);
+    let _ = extractor.extractor.extract_with_report().await.unwrap();
+
+    let mut requested = transport.requests();
+    requested.sort();
+    let expected = |from, to| format!("https://api.polygon.io{}", AggQuery::new("AAPL", 1, Timespan::Day, from, to).to_url());
+    assert_eq!(requested, vec![expected(date(2024, 1, 2), date(2024, 1, 2)), expected(date(2024, 1, 4), date(2024, 1, 5))]);
+}
@@ -0,0 +1,224 @@
+// tests/common/mod.rs
+//
+// Helpers shared by the offline integration tests.
+
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use polyextract::{
+    AggQuery, BarStore, FixtureTransport, MarketTimezone, PolyAggInfo, PolygonHistorySession, RecordingTransport, ReplayTransport,
+    ReqwestTransport, SessionConfig, TickerManager, Timespan, Transport, TransportResponse, BAR_COLUMNS,
+};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub fn test_config() -> SessionConfig {
+    SessionConfig::builder().api_key("test-key").build().unwrap()
+}
+
+/// A session backed by a cassette in `tests/cassettes`.
+pub struct CassetteSession {
+    pub session: Arc<PolygonHistorySession>,
+    recorder: Option<Arc<RecordingTransport>>,
+}
+
+impl CassetteSession {
+    /// Writes the cassette if the session was recording.
+    pub fn finish(&self) {
+        if let Some(recorder) = &self.recorder {
+            recorder.save().unwrap();
+        }
+    }
+}
+
+/// Returns a session replaying `tests/cassettes/{name}.json`.
+///
+/// When the cassette does not exist yet, or `POLYEXTRACT_RECORD` is set, the session talks to
+/// Polygon with `POLYGON_API_KEY` instead and records the cassette on `finish`. Tests run in
+/// parallel, so every test needs a cassette name of its own. The committed cassettes are
+/// synthetic; see `tests/cassettes/README.md`.
+pub fn cassette_session(name: &str) -> CassetteSession {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cassettes").join(format!("{}.json", name));
+
+    if path.exists() && std::env::var_os("POLYEXTRACT_RECORD").is_none() {
+        let transport = Arc::new(ReplayTransport::from_file(&path).unwrap());
+        let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport));
+        return CassetteSession { session, recorder: None };
+    }
+
+    let config = SessionConfig::from_env()
+        .unwrap_or_else(|_| panic!("Cassette {} is missing; set POLYGON_API_KEY to record it", path.display()));
+    let transport = Arc::new(ReqwestTransport::from_config(&config).unwrap());
+    let recorder = Arc::new(RecordingTransport::new(transport, path));
+    let session = Arc::new(PolygonHistorySession::with_transport(config, recorder.clone()));
+    CassetteSession { session, recorder: Some(recorder) }
+}
+
+/// Returns every bar replayed by `tests/cassettes/{name}.json`, in the order of its responses.
+pub fn cassette_bars(name: &str) -> Vec<serde_json::Value> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cassettes").join(format!("{}.json", name));
+    let cassette: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    cassette["interactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|interaction| {
+            let body: serde_json::Value = serde_json::from_str(interaction["body"].as_str().unwrap()).unwrap();
+            body["results"].as_array().cloned().unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Returns a session that talks to Polygon with `POLYGON_API_KEY`, for tests that need live data.
+pub fn live_session() -> Arc<PolygonHistorySession> {
+    let config = SessionConfig::from_env().expect("POLYGON_API_KEY must be set for live tests");
+    Arc::new(PolygonHistorySession::with_config(config).unwrap())
+}
+
+/// Returns an empty directory under the system temp dir, private to the calling test.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("polyextract-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+pub fn poly_agg_info(ticker: &str, start_date: NaiveDate, end_date: NaiveDate) -> PolyAggInfo {
+    PolyAggInfo {
+        ticker: ticker.to_string(),
+        start_date,
+        end_date,
+        resolution: "minute".to_string(),
+        multiplier: 1,
+    }
+}
+
+/// Returns the URL the extractor requests for one day of one minute bars.
+pub fn aggs_url(ticker: &str, date: &str) -> String {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+    format!("https://api.polygon.io{}", AggQuery::new(ticker, 1, Timespan::Minute, date, date).to_url())
+}
+
+/// Returns the millisecond timestamp of the regular session open on the given date.
+pub fn market_open(date: &str) -> i64 {
+    MarketTimezone::Eastern.market_hours_on_date_millis(date).unwrap().0
+}
+
+/// Generates `count` consecutive one minute bars starting at `start_ts`.
+pub fn bars(start_ts: i64, count: usize) -> Vec<serde_json::Value> {
+    (0..count)
+        .map(|i| {
+            let price = 100.0 + (i % 7) as f64 * 0.1;
+            json!({
+                "v": 1000 + i as i64,
+                "vw": price,
+                "o": price,
+                "c": price + 0.05,
+                "h": price + 0.1,
+                "l": price - 0.1,
+                "t": start_ts + i as i64 * 60_000,
+                "n": 10,
+            })
+        })
+        .collect()
+}
+
+/// Wraps bars in a successful Polygon aggregates response.
+pub fn page(ticker: &str, results: Vec<serde_json::Value>, next_url: Option<&str>) -> TransportResponse {
+    let mut body = json!({
+        "ticker": ticker,
+        "status": "OK",
+        "resultsCount": results.len(),
+        "results": results,
+    });
+    if let Some(next_url) = next_url {
+        body["next_url"] = json!(next_url);
+    }
+    TransportResponse::new(200, body.to_string())
+}
+
+/// A session serving `count` bars from the open of every given date for every given ticker.
+pub fn bars_session(tickers: &[&str], dates: &[&str], count: usize) -> Arc<PolygonHistorySession> {
+    let transport = FixtureTransport::new();
+    for ticker in tickers {
+        for date in dates {
+            transport.insert(&aggs_url(ticker, date), page(ticker, bars(market_open(date), count), None));
+        }
+    }
+    Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)))
+}
+
+/// Appends processed bars of AAPL and MSFT around a month end, then checks that the store reads
+/// AAPL's back unchanged.
+pub async fn check_store_read(store: &dyn BarStore) {
+    let processed_bars = |ticker: &'static str| async move {
+        let session = bars_session(&[ticker], &["2024-01-31", "2024-02-01", "2024-02-02"], 5);
+        TickerManager::new(poly_agg_info(ticker, date(2024, 1, 31), date(2024, 2, 2)), session)
+            .process_data()
+            .await
+            .unwrap()
+    };
+    let aapl = processed_bars("AAPL").await;
+    store.append(&aapl).await.unwrap();
+    store.append(&processed_bars("MSFT").await).await.unwrap();
+
+    let all = store.read("AAPL", date(2024, 1, 1)..=date(2024, 12, 31)).await.unwrap();
+    assert_eq!(all.get_column_names(), BAR_COLUMNS);
+    assert!(all.equals(&aapl));
+
+    let day = store.read("AAPL", date(2024, 2, 1)..=date(2024, 2, 1)).await.unwrap();
+    assert!(day.equals(&aapl.slice(5, 5)));
+
+    let unbounded = store.read("AAPL", NaiveDate::MIN..=NaiveDate::MAX).await.unwrap();
+    assert!(unbounded.equals(&aapl));
+
+    let january = store.read("AAPL", date(2023, 6, 15)..=date(2024, 1, 31)).await.unwrap();
+    assert!(january.equals(&aapl.slice(0, 5)));
+
+    let none = store.read("NVDA", date(2024, 1, 1)..=date(2024, 12, 31)).await.unwrap();
+    assert_eq!(none.height(), 0);
+    assert_eq!(none.schema(), aapl.schema());
+}
+
+/// Appends more bars than fit in one statement, each of them twice in the same frame, then checks
+/// that the store keeps one row per bar.
+pub async fn check_store_batches(store: &dyn BarStore) {
+    let dates = ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"];
+    let session = bars_session(&["AAPL"], &dates, 391);
+    let bars = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5)), session)
+        .process_data()
+        .await
+        .unwrap();
+    assert_eq!(bars.height(), 1564);
+
+    store.append(&bars.vstack(&bars).unwrap()).await.unwrap();
+
+    let stored = store.read("AAPL", date(2024, 1, 2)..=date(2024, 1, 5)).await.unwrap();
+    assert!(stored.equals(&bars));
+}
+
+/// Serves fixtures after a second, keeping track of how many requests were in flight at once.
+#[derive(Default)]
+pub struct SlowTransport {
+    pub fixtures: FixtureTransport,
+    in_flight: AtomicUsize,
+    pub max_in_flight: AtomicUsize,
+}
+
+#[async_trait]
+impl Transport for SlowTransport {
+    async fn get(&self, url: &str) -> polyextract::Result<TransportResponse> {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.fixtures.get(url).await
+    }
+}
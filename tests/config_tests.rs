@@ -0,0 +1,66 @@
+// tests/config_tests.rs
+
+use polyextract::{Error, SessionConfig};
+use std::time::Duration;
+
+#[test]
+fn test_session_config_explicit_values() {
+    let config = SessionConfig::builder()
+        .api_key("explicit-key")
+        .base_url("http://127.0.0.1:8080/")
+        .requests_per_second(5)
+        .max_burst_requests(2)
+        .request_timeout(Duration::from_secs(3))
+        .build()
+        .unwrap();
+
+    assert_eq!(config.api_key, "explicit-key");
+    assert_eq!(config.base_url, "http://127.0.0.1:8080");
+    assert_eq!(config.requests_per_second, 5);
+    assert_eq!(config.max_burst_requests, 2);
+    assert_eq!(config.request_timeout, Duration::from_secs(3));
+    assert!(!format!("{:?}", config).contains("explicit-key"));
+}
+
+#[test]
+fn test_session_config_from_toml_file() {
+    let path = std::env::temp_dir().join(format!("polyextract_config_{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "api_key = \"file-key\"\nrequests_per_second = 10\nconnect_timeout_secs = 2.5\n",
+    )
+    .unwrap();
+
+    let from_file = SessionConfig::from_file(&path).unwrap();
+    let overridden = SessionConfig::builder()
+        .config_file(&path)
+        .requests_per_second(20)
+        .build()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(from_file.api_key, "file-key");
+    assert_eq!(from_file.requests_per_second, 10);
+    assert_eq!(from_file.connect_timeout, Duration::from_millis(2500));
+    assert_eq!(from_file.base_url, "https://api.polygon.io");
+    assert_eq!(overridden.requests_per_second, 20);
+}
+
+#[test]
+fn test_session_config_rejects_zero_rate() {
+    let result = SessionConfig::builder().api_key("key").requests_per_second(0).build();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_session_config_rejects_invalid_timeouts() {
+    for (i, timeout) in ["-1.0", "nan", "inf"].iter().enumerate() {
+        let path = std::env::temp_dir().join(format!("polyextract_timeout_{}_{}.toml", std::process::id(), i));
+        std::fs::write(&path, format!("api_key = \"file-key\"\nrequest_timeout_secs = {}\n", timeout)).unwrap();
+
+        let result = SessionConfig::from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(Error::Config { .. })), "{} was accepted", timeout);
+    }
+}
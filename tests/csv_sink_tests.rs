@@ -0,0 +1,98 @@
+// tests/csv_sink_tests.rs
+
+#![cfg(feature = "csv")]
+
+mod common;
+
+use common::{bars_session, date, poly_agg_info, temp_dir};
+use polyextract::{CsvSink, Error, Sink, TickerManager, TimestampFormat};
+use std::sync::Arc;
+
+const COLUMNS: [&str; 10] = ["volume", "vwap", "open", "close", "high", "low", "time", "transactions", "mkt_date", "ticker"];
+
+/// Returns the value of the named column in a CSV line.
+fn field(line: &str, separator: char, column: &str) -> String {
+    let index = COLUMNS.iter().position(|name| *name == column).unwrap();
+    line.split(separator).nth(index).unwrap().to_string()
+}
+
+async fn processed_bars() -> polars::prelude::DataFrame {
+    let session = bars_session(&["AAPL"], &["2024-01-02", "2024-01-03"], 5);
+    TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3)), session)
+        .process_data()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_csv_files_keep_the_extractor_schema() {
+    let dir = temp_dir("csv-schema");
+    let sink = CsvSink::new(&dir);
+    let session = bars_session(&["AAPL"], &["2024-01-02", "2024-01-03"], 5);
+    let df = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3)), session)
+        .with_sink(Arc::new(sink.clone()))
+        .process_data()
+        .await
+        .unwrap();
+
+    assert!(dir.join("ticker=AAPL/year=2024/month=01/2024-01-03.csv").exists());
+    let csv = std::fs::read_to_string(sink.path("AAPL", date(2024, 1, 2))).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(df.get_column_names(), COLUMNS);
+    assert_eq!(lines[0], COLUMNS.join(","));
+    let first_time = df.column("time").unwrap().i64().unwrap().get(0).unwrap();
+    assert_eq!(field(lines[1], ',', "time"), first_time.to_string());
+}
+
+#[tokio::test]
+async fn test_csv_timestamps_are_formatted_in_the_timezone() {
+    let dir = temp_dir("csv-timestamps");
+    let df = processed_bars().await;
+
+    let rfc3339 = CsvSink::new(dir.join("rfc3339")).timestamps(TimestampFormat::Rfc3339).timezone(chrono_tz::America::New_York);
+    rfc3339.write(&df).await.unwrap();
+    let csv = std::fs::read_to_string(rfc3339.path("AAPL", date(2024, 1, 2))).unwrap();
+    assert_eq!(field(csv.lines().nth(1).unwrap(), ',', "time"), "2024-01-02T09:30:00.000-05:00");
+
+    let datetime = CsvSink::new(dir.join("datetime")).timestamps(TimestampFormat::Datetime).timezone(chrono_tz::America::New_York);
+    datetime.write(&df).await.unwrap();
+    let csv = std::fs::read_to_string(datetime.path("AAPL", date(2024, 1, 2))).unwrap();
+    assert_eq!(field(csv.lines().nth(1).unwrap(), ',', "time"), "2024-01-02T09:30:00.000-0500");
+
+    let strftime = CsvSink::new(dir.join("strftime"))
+        .timestamps(TimestampFormat::Strftime("%Y-%m-%d %H:%M".to_string()))
+        .separator(b';')
+        .include_header(false);
+    strftime.write(&df).await.unwrap();
+    let csv = std::fs::read_to_string(strftime.path("AAPL", date(2024, 1, 3))).unwrap();
+    assert_eq!(field(csv.lines().next().unwrap(), ';', "time"), "2024-01-03 14:30");
+}
+
+#[tokio::test]
+async fn test_rewriting_a_day_replaces_its_file() {
+    let dir = temp_dir("csv-rewrite");
+    let sink = CsvSink::new(&dir);
+    let df = processed_bars().await;
+
+    sink.write(&df).await.unwrap();
+    sink.write(&df.slice(0, 2)).await.unwrap();
+
+    let csv = std::fs::read_to_string(sink.path("AAPL", date(2024, 1, 2))).unwrap();
+    assert_eq!(csv.lines().count(), 3);
+    let csv = std::fs::read_to_string(sink.path("AAPL", date(2024, 1, 3))).unwrap();
+    assert_eq!(csv.lines().count(), 6);
+}
+
+
+#[tokio::test]
+async fn test_invalid_strftime_formats_fail_without_writing() {
+    let dir = temp_dir("csv-invalid-strftime");
+    let df = processed_bars().await;
+    let timestamps = TimestampFormat::Strftime("%Y-%m-%d %Q".to_string());
+    assert!(timestamps.validate().is_err());
+
+    let sink = CsvSink::new(&dir).timestamps(timestamps);
+    assert!(matches!(sink.write(&df).await, Err(Error::InvalidInput { .. })));
+    assert!(!sink.path("AAPL", date(2024, 1, 2)).exists());
+}
@@ -0,0 +1,60 @@
+// tests/duckdb_store_tests.rs
+
+#![cfg(feature = "store-duckdb")]
+
+mod common;
+
+use common::{bars_session, check_store_batches, check_store_read, date, poly_agg_info, temp_dir};
+use polyextract::{BarStore, DuckDbStore, TickerManager, Timespan, Updater};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_duckdb_store_reads_back_processed_bars() {
+    check_store_read(&DuckDbStore::in_memory(1, Timespan::Minute).unwrap()).await;
+}
+
+#[tokio::test]
+async fn test_duckdb_store_upserts_in_batches() {
+    check_store_batches(&DuckDbStore::in_memory(1, Timespan::Minute).unwrap()).await;
+}
+
+#[tokio::test]
+async fn test_reprocessed_days_replace_their_rows() {
+    let dir = temp_dir("duckdb-upsert");
+    std::fs::create_dir_all(&dir).unwrap();
+    let store = Arc::new(DuckDbStore::open(dir.join("bars.duckdb"), 1, Timespan::Minute).unwrap());
+    let session = bars_session(&["AAPL"], &["2024-01-02", "2024-01-03"], 5);
+    let manager = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3)), session)
+        .with_sink(store.clone());
+
+    let df = manager.process_data().await.unwrap();
+    manager.process_data().await.unwrap();
+    let mut reprocessed = df.slice(0, 5);
+    reprocessed.with_column(reprocessed.column("vwap").unwrap() * 2.0).unwrap();
+    store.append(&reprocessed).await.unwrap();
+
+    let stored = store.read("AAPL", date(2024, 1, 2)..=date(2024, 1, 3)).await.unwrap();
+    assert_eq!(stored.height(), 10);
+    assert!(stored.slice(0, 5).equals(&reprocessed));
+    assert!(stored.slice(5, 5).equals(&df.slice(5, 5)));
+
+    // Other resolutions in the same database are kept apart
+    let hourly = DuckDbStore::open(dir.join("bars.duckdb"), 1, Timespan::Hour).unwrap();
+    assert_eq!(hourly.last_date("AAPL").await.unwrap(), None);
+    assert_eq!(store.last_date("AAPL").await.unwrap(), Some(date(2024, 1, 3)));
+}
+
+#[tokio::test]
+async fn test_duckdb_store_can_be_updated() {
+    let store = DuckDbStore::in_memory(1, Timespan::Minute).unwrap();
+    let session = bars_session(&["AAPL"], &["2024-01-02", "2024-01-03"], 5);
+    let now = chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 1, 3, 22, 0, 0).unwrap();
+
+    Updater::new(session, "minute", 1)
+        .with_first_date(date(2024, 1, 2))
+        .update_as_of(&store, &["AAPL"], now)
+        .await
+        .unwrap();
+
+    assert_eq!(store.read("AAPL", date(2024, 1, 2)..=date(2024, 1, 3)).await.unwrap().height(), 10);
+}
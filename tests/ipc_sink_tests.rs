@@ -0,0 +1,49 @@
+// tests/ipc_sink_tests.rs
+
+#![cfg(feature = "ipc")]
+
+mod common;
+
+use common::{bars_session, date, poly_agg_info, temp_dir};
+use polars::prelude::*;
+use polyextract::{IpcSink, Sink, TickerManager, TimestampFormat};
+use std::fs::File;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_ipc_files_load_back_unchanged() {
+    let dir = temp_dir("ipc-roundtrip");
+    let sink = IpcSink::new(&dir);
+    let session = bars_session(&["AAPL"], &["2024-01-02"], 5);
+    let df = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), session)
+        .with_sink(Arc::new(sink.clone()))
+        .process_data()
+        .await
+        .unwrap();
+
+    let loaded = IpcReader::new(File::open(sink.path("AAPL", date(2024, 1, 2))).unwrap()).finish().unwrap();
+    assert!(loaded.equals(&df));
+}
+
+#[tokio::test]
+async fn test_ipc_timestamps_can_be_native_datetimes() {
+    let dir = temp_dir("ipc-datetime");
+    let sink = IpcSink::new(&dir)
+        .timestamps(TimestampFormat::Datetime)
+        .timezone(chrono_tz::America::New_York)
+        .compression(IpcCompression::ZSTD);
+    let session = bars_session(&["AAPL"], &["2024-01-02"], 5);
+    let df = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), session)
+        .process_data()
+        .await
+        .unwrap();
+
+    sink.write(&df).await.unwrap();
+
+    let loaded = IpcReader::new(File::open(sink.path("AAPL", date(2024, 1, 2))).unwrap()).finish().unwrap();
+    assert_eq!(
+        loaded.column("time").unwrap().dtype(),
+        &DataType::Datetime(TimeUnit::Milliseconds, Some("America/New_York".to_string()))
+    );
+    assert!(loaded.column("time").unwrap().cast(&DataType::Int64).unwrap().equals(df.column("time").unwrap()));
+}
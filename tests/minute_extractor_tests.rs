@@ -1,11 +1,16 @@
 // tests/minute_extractor_tests.rs
+
+mod common;
+
 use chrono::NaiveDate;
+use common::live_session;
 use polyextract::MinuteExtractor;
 use polyextract::PolyAggInfo;
+use std::sync::Arc;
 use std::time::Instant;
 
-
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_minute_extractor() {
     let start_time = Instant::now(); // Record the start time
 
@@ -19,7 +24,7 @@ async fn test_minute_extractor() {
         multiplier: 1,
     };
 
-    let minute_extractor = MinuteExtractor::new(poly_agg_info);
+    let minute_extractor = MinuteExtractor::new(poly_agg_info, live_session());
     let result = minute_extractor.extractor.extract().await;
 
     match result {
@@ -38,6 +43,7 @@ async fn test_minute_extractor() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_minute_extractor_concurrent_multiple_tickers() {
     let start_time = Instant::now();
 
@@ -45,6 +51,7 @@ async fn test_minute_extractor_concurrent_multiple_tickers() {
     let start_date = NaiveDate::from_ymd_opt(2007, 1, 1).unwrap();
     let end_date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
 
+    let session = live_session();
     let futures = tickers.into_iter().map(|ticker| {
         let poly_agg_info = PolyAggInfo {
             ticker: ticker.to_string(),
@@ -54,7 +61,7 @@ async fn test_minute_extractor_concurrent_multiple_tickers() {
             multiplier: 1,
         };
 
-        let minute_extractor = MinuteExtractor::new(poly_agg_info);
+        let minute_extractor = MinuteExtractor::new(poly_agg_info, Arc::clone(&session));
         async move {
             minute_extractor.extractor.extract().await
         }
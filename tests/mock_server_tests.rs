@@ -0,0 +1,97 @@
+// tests/mock_server_tests.rs
+
+#![cfg(feature = "mock-server")]
+
+mod common;
+
+use common::{date, poly_agg_info};
+use polyextract::{
+    DateStatus, Error, Fault, MarketTimezone, MinuteExtractor, MockPolygonServer, PolygonHistorySession, Processor,
+    SessionConfig,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn mock_session(server: &MockPolygonServer) -> Arc<PolygonHistorySession> {
+    let config = SessionConfig::builder().api_key("test-key").base_url(server.base_url()).build().unwrap();
+    Arc::new(PolygonHistorySession::with_config(config).unwrap())
+}
+
+#[tokio::test]
+async fn test_mock_server_paginates_with_next_url() {
+    let server = MockPolygonServer::builder().seed(7).page_size(250).start().await.unwrap();
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), mock_session(&server));
+
+    let (mut df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    // 4:00 to 20:00 of extended hours minute bars, in pages of 250
+    assert_eq!(df.height(), 960);
+    assert_eq!(report.dates[0].pages, 4);
+    assert_eq!(server.requests().len(), 4);
+    assert!(server.requests().iter().all(|request| !request.contains("test-key")));
+
+    Processor::new(&mut df, &MarketTimezone::Eastern).process().unwrap();
+    assert_eq!(df.height(), 391);
+}
+
+#[tokio::test]
+async fn test_mock_server_faults_are_retried() {
+    let server = MockPolygonServer::builder()
+        .fail_date(date(2024, 1, 2), Fault::RateLimited { retry_after: Some(1) }, 1)
+        .fail_date(date(2024, 1, 3), Fault::ServerError(503), 2)
+        .fail_date(date(2024, 1, 4), Fault::MalformedJson, 1)
+        .empty_date(date(2024, 1, 5))
+        .start()
+        .await
+        .unwrap();
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5)), mock_session(&server));
+
+    let (df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    let retries: Vec<_> = report.dates.iter().map(|date| (date.date.as_str(), date.status, date.retries)).collect();
+    assert_eq!(
+        retries,
+        vec![
+            ("2024-01-02", DateStatus::Succeeded, 1),
+            ("2024-01-03", DateStatus::Succeeded, 2),
+            ("2024-01-04", DateStatus::Succeeded, 1),
+            ("2024-01-05", DateStatus::Empty, 0),
+        ]
+    );
+    assert_eq!(df.height(), 3 * 960);
+}
+
+#[tokio::test]
+async fn test_mock_server_is_deterministic() {
+    let poly_agg_info = poly_agg_info("X:BTCUSD", date(2024, 1, 1), date(2024, 1, 1));
+    let mut dataframes = Vec::new();
+    for seed in [1, 1, 2] {
+        let server = MockPolygonServer::builder().seed(seed).start().await.unwrap();
+        let extractor = MinuteExtractor::new(poly_agg_info.clone(), mock_session(&server));
+        dataframes.push(extractor.extractor.extract().await.unwrap());
+    }
+
+    // Crypto trades around the clock, holidays included
+    assert_eq!(dataframes[0].height(), 1440);
+    assert_eq!(dataframes[0], dataframes[1]);
+    assert_ne!(dataframes[0], dataframes[2]);
+}
+
+#[tokio::test]
+async fn test_mock_server_rejects_unknown_api_key() {
+    let server = MockPolygonServer::builder().api_key("another-key").start().await.unwrap();
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), mock_session(&server));
+
+    assert!(matches!(extractor.extractor.extract().await, Err(Error::Unauthorized { status: 401, .. })));
+}
+
+#[tokio::test]
+async fn test_mock_server_latency() {
+    let server = MockPolygonServer::builder().latency(Duration::from_millis(300)).start().await.unwrap();
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), mock_session(&server));
+
+    let started = Instant::now();
+    extractor.extractor.extract().await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(300));
+}
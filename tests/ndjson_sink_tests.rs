@@ -0,0 +1,48 @@
+// tests/ndjson_sink_tests.rs
+
+#![cfg(feature = "ndjson")]
+
+mod common;
+
+use common::{bars_session, date, poly_agg_info, temp_dir};
+use polyextract::{NdjsonSink, TickerManager, TimestampFormat};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_ndjson_files_hold_one_bar_per_line() {
+    let dir = temp_dir("ndjson");
+    let sink = NdjsonSink::new(&dir).timestamps(TimestampFormat::Rfc3339);
+    let session = bars_session(&["AAPL"], &["2024-01-02"], 5);
+    let df = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), session)
+        .with_sink(Arc::new(sink.clone()))
+        .process_data()
+        .await
+        .unwrap();
+
+    let ndjson = std::fs::read_to_string(sink.path("AAPL", date(2024, 1, 2))).unwrap();
+    let bars: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(bars.len(), 5);
+
+    let fields: Vec<&str> = bars[0].as_object().unwrap().keys().map(String::as_str).collect();
+    let mut columns = df.get_column_names();
+    columns.sort();
+    assert_eq!(fields, columns);
+    assert_eq!(bars[0]["time"], "2024-01-02T14:30:00.000+00:00");
+    assert_eq!(bars[0]["ticker"], "AAPL");
+}
+
+#[tokio::test]
+async fn test_ndjson_datetimes_are_written_as_rfc3339() {
+    let dir = temp_dir("ndjson-datetime");
+    let sink = NdjsonSink::new(&dir).timestamps(TimestampFormat::Datetime).timezone(chrono_tz::America::New_York);
+    let session = bars_session(&["AAPL"], &["2024-01-02"], 5);
+    TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 2)), session)
+        .with_sink(Arc::new(sink.clone()))
+        .process_data()
+        .await
+        .unwrap();
+
+    let ndjson = std::fs::read_to_string(sink.path("AAPL", date(2024, 1, 2))).unwrap();
+    let bar: serde_json::Value = serde_json::from_str(ndjson.lines().next().unwrap()).unwrap();
+    assert_eq!(bar["time"], "2024-01-02T09:30:00.000-05:00");
+}
@@ -0,0 +1,74 @@
+// tests/parquet_sink_tests.rs
+
+mod common;
+
+use common::{bars_session, date, poly_agg_info, temp_dir};
+use polars::prelude::*;
+use polyextract::{BarStore, ParquetSink, PolygonHistorySession, Sink, TickerManager, TickerManagerPool};
+use std::fs::File;
+use std::sync::Arc;
+
+const DATES: [&str; 2] = ["2024-01-31", "2024-02-01"];
+
+fn session(tickers: &[&str]) -> Arc<PolygonHistorySession> {
+    bars_session(tickers, &DATES, 30)
+}
+
+fn read(path: &std::path::Path) -> DataFrame {
+    ParquetReader::new(File::open(path).unwrap()).finish().unwrap()
+}
+
+#[tokio::test]
+async fn test_processed_bars_are_partitioned_by_ticker_and_month() {
+    let dir = temp_dir("parquet-partitions");
+    let sink = ParquetSink::new(&dir);
+    let poly_agg_infos = vec![
+        poly_agg_info("AAPL", date(2024, 1, 31), date(2024, 2, 1)),
+        poly_agg_info("MSFT", date(2024, 1, 31), date(2024, 2, 1)),
+    ];
+
+    let dfs = TickerManagerPool::new(poly_agg_infos, session(&["AAPL", "MSFT"]))
+        .with_sink(Arc::new(sink.clone()))
+        .process_data_concurrently()
+        .await
+        .unwrap();
+
+    assert!(dir.join("ticker=AAPL/year=2024/month=01/data.parquet").exists());
+    assert!(dir.join("ticker=MSFT/year=2024/month=02/data.parquet").exists());
+
+    let january = read(&sink.path("AAPL", 2024, 1));
+    assert_eq!(january.height(), 30);
+    assert_eq!(january.get_column_names(), dfs[0].get_column_names());
+    assert!(january.equals(&dfs[0].head(Some(30))));
+    assert_eq!(sink.last_date("MSFT").await.unwrap(), Some(date(2024, 2, 1)));
+}
+
+#[tokio::test]
+async fn test_rewriting_bars_replaces_them() {
+    let dir = temp_dir("parquet-rewrite");
+    let sink = Arc::new(ParquetSink::new(&dir));
+    let manager = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 31), date(2024, 2, 1)), session(&["AAPL"]))
+        .with_sink(sink.clone());
+
+    let df = manager.process_data().await.unwrap();
+    manager.process_data().await.unwrap();
+    sink.write(&df.slice(30, 10)).await.unwrap();
+
+    assert_eq!(read(&sink.path("AAPL", 2024, 1)).height(), 30);
+    assert!(read(&sink.path("AAPL", 2024, 2)).equals(&df.slice(30, 30)));
+}
+
+#[tokio::test]
+async fn test_compression_and_row_group_settings() {
+    let dir = temp_dir("parquet-settings");
+    let sink = ParquetSink::new(&dir).compression(ParquetCompression::Snappy).row_group_size(10);
+    let manager = TickerManager::new(poly_agg_info("AAPL", date(2024, 1, 31), date(2024, 1, 31)), session(&["AAPL"]))
+        .with_sink(Arc::new(sink.clone()));
+
+    let df = manager.process_data().await.unwrap();
+
+    let mut reader = ParquetReader::new(File::open(sink.path("AAPL", 2024, 1)).unwrap());
+    let metadata = reader.get_metadata().unwrap();
+    assert_eq!(metadata.row_groups.len(), 3);
+    assert!(reader.finish().unwrap().equals(&df));
+}
@@ -1,7 +1,11 @@
 // tests/processor_tests.rs
+
+mod common;
+
 use chrono::{NaiveDate, NaiveDateTime, TimeZone};
 use chrono_tz::US::Eastern;
-use polyextract::{MarketTimezone, MinuteExtractor, PolyAggInfo, Processor};
+use common::{cassette_bars, cassette_session};
+use polyextract::{Error, MarketTimezone, MinuteExtractor, PolyAggInfo, Processor};
 use std::time::Instant;
 use polyextract::processor::MarketHoursFilter;
 
@@ -37,6 +41,39 @@ async fn test_timezone_timestamps() {
     assert_eq!(actual_end_timestamp, expected_end_timestamp);
 }
 
+#[test]
+fn test_timezone_invalid_date() {
+    let market_timezone = MarketTimezone::Eastern;
+
+    match market_timezone.market_hours_on_date("2024-13-01") {
+        Err(Error::Calendar { context, .. }) => assert_eq!(context.date.as_deref(), Some("2024-13-01")),
+        other => panic!("Expected a calendar error, got {:?}", other),
+    }
+}
+
+
+/// Counts the cassette's bars inside the regular session on `dates`, and how many of those have a
+/// wick over a dollar. The synthetic cassettes only blow wicks out far past that.
+fn regular_session_bars(cassette: &str, dates: &[&str]) -> (usize, usize) {
+    let sessions: Vec<_> =
+        dates.iter().map(|date| MarketTimezone::Eastern.market_hours_on_date_millis(date).unwrap()).collect();
+    let regular: Vec<_> = cassette_bars(cassette)
+        .into_iter()
+        .filter(|bar| {
+            let time = bar["t"].as_i64().unwrap();
+            sessions.iter().any(|(start, end)| time >= *start && time <= *end)
+        })
+        .collect();
+    let blown_out = regular
+        .iter()
+        .filter(|bar| {
+            let price = |key: &str| bar[key].as_f64().unwrap();
+            let (open, close, high, low) = (price("o"), price("c"), price("h"), price("l"));
+            high - open.max(close) > 1.0 || open.min(close) - low > 1.0
+        })
+        .count();
+    (regular.len(), blown_out)
+}
 
 #[tokio::test]
 async fn test_time_filter_single_day() {
@@ -52,8 +89,10 @@ async fn test_time_filter_single_day() {
         multiplier: 1,
     };
 
-    let minute_extractor = MinuteExtractor::new(poly_agg_info);
+    let cassette = cassette_session("aapl_minute_2024_01_02_filter");
+    let minute_extractor = MinuteExtractor::new(poly_agg_info, cassette.session.clone());
     let df = minute_extractor.extractor.extract().await;
+    cassette.finish();
 
     // Create a mutable variable to hold the unwrapped DataFrame
     let unwrapped_df = df.unwrap();
@@ -73,7 +112,9 @@ async fn test_time_filter_single_day() {
         opt_time.map(|time| time >= market_start && time <= market_end).unwrap_or(false)
     }));
 
-    assert_eq!(filtered_df.height(), 391);
+    let (regular, _) = regular_session_bars("aapl_minute_2024_01_02_filter", &["2024-01-02"]);
+    assert_eq!(filtered_df.height(), regular);
+    assert!(filtered_df.height() < unwrapped_df.height());
 
     let elapsed_time = start_time.elapsed().as_secs_f64(); // Calculate the elapsed time in seconds
     println!("Test execution time: {:.2} seconds", elapsed_time);
@@ -94,8 +135,10 @@ async fn test_processor_single_day() {
         multiplier: 1,
     };
 
-    let minute_extractor = MinuteExtractor::new(poly_agg_info);
+    let cassette = cassette_session("aapl_minute_2024_01_02");
+    let minute_extractor = MinuteExtractor::new(poly_agg_info, cassette.session.clone());
     let df = minute_extractor.extractor.extract().await;
+    cassette.finish();
 
     // Create a mutable variable to hold the unwrapped DataFrame
     let mut unwrapped_df = df.unwrap();
@@ -107,12 +150,13 @@ async fn test_processor_single_day() {
     // Process the DataFrame
     let (p1_outliers, p2_outliers) = processor.process().unwrap();
 
-    // Check the sum of outliers detected
+    // Check the sum of outliers detected against the blown-out wicks in the cassette
+    let (regular, blown_out) = regular_session_bars("aapl_minute_2024_01_02", &["2024-01-02"]);
     let total_outliers = p1_outliers + p2_outliers;
-    assert_eq!(total_outliers, 4);
+    assert_eq!(total_outliers, blown_out);
 
     // Check the length of the processed DataFrame
-    assert_eq!(processor.df.height(), 391);
+    assert_eq!(processor.df.height(), regular);
 
     let elapsed_time = start_time.elapsed().as_secs_f64(); // Calculate the elapsed time in seconds
     println!("Test execution time: {:.2} seconds", elapsed_time);
@@ -132,8 +176,10 @@ async fn test_processor_multiple_days() {
         multiplier: 1,
     };
 
-    let minute_extractor = MinuteExtractor::new(poly_agg_info);
+    let cassette = cassette_session("aapl_minute_2024_01_02_to_03");
+    let minute_extractor = MinuteExtractor::new(poly_agg_info, cassette.session.clone());
     let df = minute_extractor.extractor.extract().await;
+    cassette.finish();
 
     // Create a mutable variable to hold the unwrapped DataFrame
     let mut unwrapped_df = df.unwrap();
@@ -145,12 +191,13 @@ async fn test_processor_multiple_days() {
     // Process the DataFrame
     let (p1_outliers, p2_outliers) = processor.process().unwrap();
 
-    // Check the sum of outliers detected
+    // Check the sum of outliers detected against the blown-out wicks in the cassette
+    let (regular, blown_out) = regular_session_bars("aapl_minute_2024_01_02_to_03", &["2024-01-02", "2024-01-03"]);
     let total_outliers = p1_outliers + p2_outliers;
-    assert_eq!(total_outliers, 7);
+    assert_eq!(total_outliers, blown_out);
 
     // Check the length of the processed DataFrame
-    assert_eq!(processor.df.height(), 782);
+    assert_eq!(processor.df.height(), regular);
 
     let elapsed_time = start_time.elapsed().as_secs_f64(); // Calculate the elapsed time in seconds
     println!("Test execution time: {:.2} seconds", elapsed_time);
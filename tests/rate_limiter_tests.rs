@@ -0,0 +1,193 @@
+// tests/rate_limiter_tests.rs
+
+use futures::future::join_all;
+use polyextract::{RateLimiter, RatePlan};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[tokio::test(start_paused = true)]
+async fn test_burst_then_sustained_rate() {
+    let limiter = RateLimiter::per_second(10, 5);
+    let started = Instant::now();
+
+    for _ in 0..5 {
+        limiter.acquire().await.unwrap();
+    }
+    assert_eq!(started.elapsed(), Duration::ZERO);
+    assert_eq!(limiter.available_tokens().unwrap(), 0);
+    assert_eq!(limiter.wait_time().unwrap(), Duration::from_millis(100));
+
+    limiter.acquire().await.unwrap();
+    assert_eq!(started.elapsed(), Duration::from_millis(100));
+
+    tokio::time::sleep(Duration::from_secs(10)).await;
+    assert_eq!(limiter.available_tokens().unwrap(), 5);
+    assert_eq!(limiter.wait_time().unwrap(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_waiters_are_served_in_order() {
+    let limiter = RateLimiter::per_second(2, 1);
+    let completed = Mutex::new(Vec::new());
+
+    join_all((0..6).map(|index| {
+        let limiter = limiter.clone();
+        let completed = &completed;
+        async move {
+            limiter.acquire().await.unwrap();
+            completed.lock().unwrap().push(index);
+        }
+    }))
+    .await;
+
+    assert_eq!(*completed.lock().unwrap(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fractional_rates_do_not_drift() {
+    let limiter = RateLimiter::per_second(3, 1);
+    let started = Instant::now();
+
+    for _ in 0..31 {
+        limiter.acquire().await.unwrap();
+    }
+
+    // 30 intervals of a third of a second
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(9_999) && elapsed <= Duration::from_millis(10_001));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_plan_presets() {
+    let basic = RateLimiter::from_plan(RatePlan::Basic);
+    let started = Instant::now();
+    for _ in 0..6 {
+        basic.acquire().await.unwrap();
+    }
+    assert_eq!(started.elapsed(), Duration::from_secs(12));
+
+    let unlimited = RateLimiter::from_plan(RatePlan::Unlimited);
+    for _ in 0..10_000 {
+        unlimited.acquire().await.unwrap();
+    }
+    assert_eq!(started.elapsed(), Duration::from_secs(12));
+    assert_eq!(unlimited.wait_time().unwrap(), Duration::ZERO);
+
+    assert_eq!(RateLimiter::from_plan(RatePlan::Starter).available_tokens().unwrap(), 100);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_clones_share_one_budget() {
+    let limiter = RateLimiter::per_second(1, 2);
+    let shared = limiter.clone();
+
+    limiter.acquire().await.unwrap();
+    shared.acquire().await.unwrap();
+
+    assert_eq!(limiter.available_tokens().unwrap(), 0);
+    assert_eq!(shared.wait_time().unwrap(), Duration::from_secs(1));
+}
+
+fn limiter_file(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("polyextract-{}-{}.limiter", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn test_shared_limiters_pause_each_other() {
+    let path = limiter_file("pause");
+    let first = RateLimiter::shared(&path, 100, Duration::from_secs(1), 10).unwrap();
+    let second = RateLimiter::shared(&path, 100, Duration::from_secs(1), 10).unwrap();
+    assert_eq!(second.available_tokens().unwrap(), 10);
+
+    first.pause(Duration::from_millis(300)).await.unwrap();
+    assert_eq!(second.available_tokens().unwrap(), 0);
+    assert!(second.wait_time().unwrap() > Duration::from_millis(200));
+
+    let started = std::time::Instant::now();
+    second.acquire().await.unwrap();
+    assert!(started.elapsed() > Duration::from_millis(200));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_pause_waits_for_the_file_lock_off_the_runtime() {
+    let path = limiter_file("pause-locked");
+    let limiter = RateLimiter::shared(&path, 100, Duration::from_secs(1), 10).unwrap();
+
+    // Another process holds the lock for a while
+    let other = std::fs::File::open(&path).unwrap();
+    fs2::FileExt::lock_exclusive(&other).unwrap();
+    let holder = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(300));
+        fs2::FileExt::unlock(&other).unwrap();
+    });
+
+    // The test runtime has a single thread, which a blocking pause would stall
+    let started = std::time::Instant::now();
+    let ticker = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        started.elapsed()
+    });
+    limiter.pause(Duration::from_millis(1)).await.unwrap();
+    holder.join().unwrap();
+
+    assert!(ticker.await.unwrap() < Duration::from_millis(200));
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_acquires_on_a_shared_limiter() {
+    let path = limiter_file("concurrent");
+    let limiter = RateLimiter::shared(&path, 100, Duration::from_secs(1), 1).unwrap();
+    let started = std::time::Instant::now();
+
+    let tasks: Vec<_> = (0..20)
+        .map(|_| {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire().await.unwrap() })
+        })
+        .collect();
+    for task in join_all(tasks).await {
+        task.unwrap();
+    }
+
+    // Every task reserved its own slot, so twenty requests at 100 per second take 19 intervals
+    assert!(started.elapsed() >= Duration::from_millis(190));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_processes_share_one_file_budget() {
+    // Run as one of the child processes spawned below
+    if let Ok(path) = std::env::var("POLYEXTRACT_LIMITER_FILE") {
+        let limiter = RateLimiter::shared(path, 10, Duration::from_secs(1), 1).unwrap();
+        for _ in 0..5 {
+            limiter.acquire().await.unwrap();
+        }
+        return;
+    }
+
+    let path = limiter_file("processes");
+    let started = std::time::Instant::now();
+    let children: Vec<_> = (0..2)
+        .map(|_| {
+            std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["test_processes_share_one_file_budget", "--exact", "--quiet"])
+                .env("POLYEXTRACT_LIMITER_FILE", &path)
+                .stdout(std::process::Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    // Ten requests at ten per second take nine intervals, while each process alone needs four
+    assert!(started.elapsed() >= Duration::from_millis(900));
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,75 @@
+// tests/report_tests.rs
+
+mod common;
+
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, test_config};
+use polyextract::{DateStatus, FixtureTransport, MinuteExtractor, PolygonHistorySession, TickerManagerPool};
+use std::sync::Arc;
+
+/// Serves 2023-12-29 empty, 2024-01-02 with data, and nothing for 2024-01-03. The weekend and
+/// New Year's Day in between are never requested.
+fn fixture_transport(tickers: &[&str]) -> FixtureTransport {
+    let transport = FixtureTransport::new();
+    for ticker in tickers {
+        transport.insert(&aggs_url(ticker, "2023-12-29"), page(ticker, Vec::new(), None));
+        transport.insert(&aggs_url(ticker, "2024-01-02"), page(ticker, bars(market_open("2024-01-02"), 391), None));
+    }
+    transport
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_extraction_report_distinguishes_empty_and_failed_dates() {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(fixture_transport(&["AAPL"]))));
+    let poly_agg_info = poly_agg_info("AAPL", date(2023, 12, 29), date(2024, 1, 3));
+
+    let extractor = MinuteExtractor::new(poly_agg_info, session);
+    let (df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(df.height(), 391);
+    assert_eq!(report.ticker, "AAPL");
+    assert_eq!(report.dates.len(), 3);
+
+    let no_trades = &report.dates[0];
+    assert_eq!(no_trades.date, "2023-12-29");
+    assert_eq!(no_trades.status, DateStatus::Empty);
+    assert_eq!(no_trades.pages, 1);
+
+    let trading_day = &report.dates[1];
+    assert_eq!(trading_day.date, "2024-01-02");
+    assert_eq!(trading_day.status, DateStatus::Succeeded);
+    assert_eq!(trading_day.bars, 391);
+    assert_eq!(trading_day.retries, 0);
+
+    let dropped = &report.dates[2];
+    assert_eq!(dropped.status, DateStatus::Failed);
+    assert_eq!(dropped.retries, 5);
+    assert!(dropped.error.is_some());
+
+    assert!(!report.is_complete());
+    assert!(extractor.extractor.extract().await.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pool_report_aggregates_tickers() {
+    let tickers = ["AAPL", "MSFT"];
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(fixture_transport(&tickers))));
+    let poly_agg_infos = tickers
+        .iter()
+        .map(|ticker| poly_agg_info(ticker, date(2023, 12, 29), date(2024, 1, 3)))
+        .collect();
+
+    let (dataframes, pool_report) = TickerManagerPool::new(poly_agg_infos, session)
+        .process_data_with_reports()
+        .await
+        .unwrap();
+
+    assert_eq!(dataframes.len(), 2);
+    assert_eq!(pool_report.succeeded(), 2);
+    assert_eq!(pool_report.empty(), 2);
+    assert_eq!(pool_report.failed(), 2);
+    assert_eq!(pool_report.total_bars(), 782);
+
+    let failed: Vec<_> = pool_report.failed_dates().iter().map(|(ticker, date)| (*ticker, date.date.as_str())).collect();
+    assert_eq!(failed, vec![("AAPL", "2024-01-03"), ("MSFT", "2024-01-03")]);
+    assert_eq!(pool_report.to_string().lines().count(), 6);
+}
@@ -0,0 +1,184 @@
+// tests/retry_tests.rs
+
+mod common;
+
+use async_trait::async_trait;
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, test_config, SlowTransport};
+use polyextract::{
+    DateStatus, Error, ErrorContext, ExtractOptions, FixtureTransport, MinuteExtractor, PolygonHistorySession,
+    RetryClass, RetryPolicy, Transport, TransportResponse,
+};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Serves fixtures, except that requests for one date never complete.
+struct HangingTransport {
+    fixtures: FixtureTransport,
+    hung_date: &'static str,
+}
+
+#[async_trait]
+impl Transport for HangingTransport {
+    async fn get(&self, url: &str) -> polyextract::Result<TransportResponse> {
+        if url.contains(self.hung_date) {
+            std::future::pending::<()>().await;
+        }
+        self.fixtures.get(url).await
+    }
+}
+
+const SLOW_DATES: [&str; 4] = ["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05"];
+
+/// Fails each of the given dates once with a 503 before serving its bars.
+fn slow_transport(failing_dates: &[&str]) -> Arc<SlowTransport> {
+    let transport = SlowTransport::default();
+    for date in SLOW_DATES {
+        let url = aggs_url("AAPL", date);
+        if failing_dates.contains(&date) {
+            transport.fixtures.insert(&url, TransportResponse::new(503, "unavailable"));
+        }
+        transport.fixtures.insert(&url, page("AAPL", bars(market_open(date), 10), None));
+    }
+    Arc::new(transport)
+}
+
+fn slow_extractor(transport: Arc<SlowTransport>, max_in_flight: usize) -> MinuteExtractor {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport));
+    let mut extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 5)), session);
+    extractor.extractor.options = ExtractOptions::default()
+        .max_in_flight(max_in_flight)
+        .retry_policy(RetryPolicy::default().base_backoff(Duration::from_millis(500)).jitter(0.0));
+    extractor
+}
+
+/// Serves 2024-01-02 with data and hangs on 2024-01-03.
+fn hanging_session() -> Arc<PolygonHistorySession> {
+    let fixtures = FixtureTransport::new()
+        .with_response(&aggs_url("AAPL", "2024-01-02"), page("AAPL", bars(market_open("2024-01-02"), 391), None));
+    let transport = HangingTransport { fixtures, hung_date: "2024-01-03" };
+    Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)))
+}
+
+#[test]
+fn test_backoff_doubles_up_to_the_maximum() {
+    let policy = RetryPolicy::default()
+        .base_backoff(Duration::from_secs(1))
+        .max_backoff(Duration::from_secs(5))
+        .jitter(0.5);
+
+    let backoffs: Vec<_> = (1..=4).map(|retry| policy.backoff(retry).as_secs()).collect();
+    assert_eq!(backoffs, vec![1, 2, 4, 5]);
+
+    for _ in 0..100 {
+        let jittered = policy.jittered_backoff(3);
+        assert!(jittered >= Duration::from_secs(2) && jittered <= Duration::from_secs(4));
+    }
+}
+
+#[test]
+fn test_out_of_range_jitter_is_clamped() {
+    let mut policy = RetryPolicy::default().base_backoff(Duration::from_secs(1));
+
+    policy.jitter = 1.5;
+    for _ in 0..100 {
+        assert!(policy.jittered_backoff(1) <= Duration::from_secs(1));
+    }
+
+    policy.jitter = f64::NAN;
+    assert_eq!(policy.jittered_backoff(1), Duration::from_secs(1));
+
+    policy.jitter = -1.0;
+    assert_eq!(policy.jittered_backoff(1), Duration::from_secs(1));
+}
+
+#[test]
+fn test_only_configured_classes_are_retried() {
+    let policy = RetryPolicy::default().retryable(&[RetryClass::RateLimited, RetryClass::Timeout]);
+    let status = |status| Error::HttpStatus { context: ErrorContext::new(), status, body: String::new(), retry_after: None };
+
+    assert!(policy.should_retry(&status(429)));
+    assert!(!policy.should_retry(&status(503)));
+    assert!(policy.should_retry(&Error::Timeout { context: ErrorContext::new(), timeout: Duration::from_secs(1) }));
+    assert!(!RetryPolicy::default().should_retry(&status(404)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_request_timeout_fails_a_hung_date() {
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3));
+    let mut extractor = MinuteExtractor::new(poly_agg_info, hanging_session());
+    extractor.extractor.options = ExtractOptions::default()
+        .request_timeout(Duration::from_secs(10))
+        .retry_policy(RetryPolicy::default().max_attempts(3));
+
+    let (df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(df.height(), 391);
+    let hung = &report.dates[1];
+    assert_eq!(hung.date, "2024-01-03");
+    assert_eq!(hung.status, DateStatus::Failed);
+    assert_eq!(hung.retries, 2);
+    assert!(hung.error.as_deref().unwrap().contains("timed out"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_deadline_ends_the_extraction() {
+    let poly_agg_info = poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3));
+    let mut extractor = MinuteExtractor::new(poly_agg_info, hanging_session());
+    extractor.extractor.options = ExtractOptions::default().deadline(Duration::from_secs(60));
+
+    let error = extractor.extractor.extract_with_report().await.unwrap_err();
+    assert!(matches!(error, Error::DeadlineExceeded { deadline, .. } if deadline == Duration::from_secs(60)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_excluded_classes_fail_their_date_without_retrying() {
+    let transport = Arc::new(
+        FixtureTransport::new()
+            .with_response(&aggs_url("AAPL", "2024-01-02"), page("AAPL", bars(market_open("2024-01-02"), 391), None))
+            .with_response(&aggs_url("AAPL", "2024-01-03"), TransportResponse::new(503, "unavailable")),
+    );
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+    let mut extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3)), session);
+    extractor.extractor.options =
+        ExtractOptions::default().retry_policy(RetryPolicy::default().retryable(&[RetryClass::RateLimited]));
+
+    let (_, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(report.dates[1].status, DateStatus::Failed);
+    assert_eq!(report.dates[1].retries, 0);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_dates_are_retried_before_later_dates() {
+    let transport = slow_transport(&["2024-01-02"]);
+    let extractor = slow_extractor(transport.clone(), 1);
+
+    let (df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(df.height(), 40);
+    assert_eq!(report.dates[0].retries, 1);
+
+    // The retry goes out as soon as its backoff is over, not after the last date
+    let requested: Vec<_> = transport
+        .fixtures
+        .requests()
+        .iter()
+        .map(|url| SLOW_DATES.iter().position(|date| url.contains(date)).unwrap())
+        .collect();
+    assert_eq!(requested, vec![0, 1, 0, 2, 3]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_retries_respect_the_in_flight_limit() {
+    let transport = slow_transport(&SLOW_DATES);
+    let extractor = slow_extractor(transport.clone(), 2);
+
+    let (df, report) = extractor.extractor.extract_with_report().await.unwrap();
+
+    assert_eq!(df.height(), 40);
+    assert!(report.dates.iter().all(|date| date.retries == 1));
+    assert_eq!(transport.fixtures.requests().len(), 8);
+    assert_eq!(transport.max_in_flight.load(Ordering::SeqCst), 2);
+}
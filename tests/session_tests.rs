@@ -1,16 +1,21 @@
+mod common;
+
 use chrono::prelude::*;
+use common::live_session;
 use futures::future::join_all;
-use polyextract::PolygonHistorySession;
+use std::sync::Arc;
 use std::time::Instant;
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_minute_request() {
     let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/minute/2023-01-09/2023-01-09?adjusted=true&sort=asc&limit=5000";
-    let response = PolygonHistorySession::send_request(url).await;
+    let session = live_session();
+    let response = session.send_request(url).await;
 
     match response {
         Ok(res) => {
-            let body = res.text().await.unwrap();
+            let body = res.body;
             println!("Response body: {}", body);
             // Assert the expected behavior or validate the response data
             assert!(body.contains("ticker"));
@@ -24,13 +29,15 @@ async fn test_polygon_api_minute_request() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_day_request() {
     let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/day/2023-01-09/2023-01-09?adjusted=true&sort=asc&limit=120";
-    let response = PolygonHistorySession::send_request(url).await;
+    let session = live_session();
+    let response = session.send_request(url).await;
 
     match response {
         Ok(res) => {
-            let body = res.text().await.unwrap();
+            let body = res.body;
             println!("Response body: {}", body);
             // Assert the expected behavior or validate the response data
             assert!(body.contains("ticker"));
@@ -44,13 +51,15 @@ async fn test_polygon_api_day_request() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_week_request() {
     let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/week/2023-01-01/2023-01-09?adjusted=true&sort=asc&limit=120";
-    let response = PolygonHistorySession::send_request(url).await;
+    let session = live_session();
+    let response = session.send_request(url).await;
 
     match response {
         Ok(res) => {
-            let body = res.text().await.unwrap();
+            let body = res.body;
             println!("Response body: {}", body);
             // Assert the expected behavior or validate the response data
             assert!(body.contains("ticker"));
@@ -64,13 +73,15 @@ async fn test_polygon_api_week_request() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_month_request() {
     let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/month/2023-01-01/2023-02-01?adjusted=true&sort=asc&limit=120";
-    let response = PolygonHistorySession::send_request(url).await;
+    let session = live_session();
+    let response = session.send_request(url).await;
 
     match response {
         Ok(res) => {
-            let body = res.text().await.unwrap();
+            let body = res.body;
             println!("Response body: {}", body);
             // Assert the expected behavior or validate the response data
             assert!(body.contains("ticker"));
@@ -84,15 +95,18 @@ async fn test_polygon_api_month_request() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_concurrent_requests() {
     let tickers = vec!["AAPL", "GOOGL", "MSFT"];
     let start_date = "2023-01-09";
     let end_date = "2023-01-09";
 
+    let session = live_session();
     let futures = tickers.into_iter().map(|ticker| {
         let url = format!("https://api.polygon.io/v2/aggs/ticker/{}/range/1/minute/{}/{}?adjusted=true&sort=asc&limit=5000", ticker, start_date, end_date);
+        let session = Arc::clone(&session);
         tokio::spawn(async move {
-            let response = PolygonHistorySession::send_request(&url).await;
+            let response = session.send_request(&url).await;
             (ticker, response)
         })
     });
@@ -102,7 +116,7 @@ async fn test_polygon_api_concurrent_requests() {
     for result in results {
         match result {
             Ok((ticker, Ok(res))) => {
-                let body = res.text().await.unwrap();
+                let body = res.body;
                 println!("Response body for {}: {}", ticker, body);
                 // Assert the expected behavior or validate the response data
                 assert!(body.contains("ticker"));
@@ -120,6 +134,7 @@ async fn test_polygon_api_concurrent_requests() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_concurrent_single_ticker_requests() {
     let ticker = "AAPL";
     let start_date = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
@@ -130,10 +145,12 @@ async fn test_polygon_api_concurrent_single_ticker_requests() {
         .map(|date| date.format("%Y-%m-%d").to_string())
         .collect::<Vec<_>>();
 
+    let session = live_session();
     let futures = date_range.into_iter().map(|date| {
         let url = format!("https://api.polygon.io/v2/aggs/ticker/{}/range/1/minute/{}/{}?adjusted=true&sort=asc&limit=5000", ticker, date, date);
+        let session = Arc::clone(&session);
         tokio::spawn(async move {
-            let response = PolygonHistorySession::send_request(&url).await;
+            let response = session.send_request(&url).await;
             (date, response)
         })
     });
@@ -143,7 +160,7 @@ async fn test_polygon_api_concurrent_single_ticker_requests() {
     for result in results {
         match result {
             Ok((_date, Ok(res))) => {
-                let body = res.text().await.unwrap();
+                let body = res.body;
                 // println!("Response body for {} on {}: {}", ticker, date, body);
                 // Assert the expected behavior or validate the response data
                 assert!(body.contains("ticker"));
@@ -161,6 +178,7 @@ async fn test_polygon_api_concurrent_single_ticker_requests() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_polygon_api_concurrent_multiple_tickers_requests() {
     let tickers = vec!["AAPL", "GOOGL", "MSFT"];
     let start_date = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
@@ -171,12 +189,15 @@ async fn test_polygon_api_concurrent_multiple_tickers_requests() {
         .map(|date| date.format("%Y-%m-%d").to_string())
         .collect::<Vec<_>>();
 
+    let session = live_session();
     let futures = tickers.into_iter().flat_map(|ticker| {
         let date_range = date_range.clone();
+        let session = Arc::clone(&session);
         date_range.into_iter().map(move |date| {
             let url = format!("https://api.polygon.io/v2/aggs/ticker/{}/range/1/minute/{}/{}?adjusted=true&sort=asc&limit=5000", ticker, date, date);
+            let session = Arc::clone(&session);
             tokio::spawn(async move {
-                let response = PolygonHistorySession::send_request(&url).await;
+                let response = session.send_request(&url).await;
                 (ticker, date, response)
             })
         })
@@ -187,7 +208,7 @@ async fn test_polygon_api_concurrent_multiple_tickers_requests() {
     for result in results {
         match result {
             Ok((ticker, _date, Ok(res))) => {
-                let body = res.text().await.unwrap();
+                let body = res.body;
                 // println!("Response body for {} on {}: {}", ticker, date, body);
                 // Assert the expected behavior or validate the response data
                 assert!(body.contains("ticker"));
@@ -205,16 +226,19 @@ async fn test_polygon_api_concurrent_multiple_tickers_requests() {
 }
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_rate_limiter() {
     let num_requests = 500;
     let expected_time = num_requests as f32 / 100.0;
 
     let start_time = Instant::now();
 
+    let session = live_session();
     let futures = (0..num_requests).map(|_| {
         let url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/minute/2023-01-01/2023-01-01?adjusted=true&sort=asc&limit=1";
+        let session = Arc::clone(&session);
         tokio::spawn(async move {
-            let response = PolygonHistorySession::send_request(url).await;
+            let response = session.send_request(url).await;
             response
         })
     });
@@ -232,8 +256,7 @@ async fn test_rate_limiter() {
     for result in results {
         match result {
             Ok(Ok(res)) => {
-                let status = res.status();
-                assert_eq!(status, reqwest::StatusCode::OK);
+                assert_eq!(res.status, 200);
             }
             Ok(Err(error)) => {
                 eprintln!("Error: {}", error);
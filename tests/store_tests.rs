@@ -0,0 +1,25 @@
+// tests/store_tests.rs
+
+mod common;
+
+use common::{check_store_read, date, temp_dir};
+use polars::prelude::*;
+use polyextract::{BarStore, MemoryBarStore, ParquetSink, BAR_COLUMNS};
+
+#[tokio::test]
+async fn test_memory_store_reads_back_processed_bars() {
+    check_store_read(&MemoryBarStore::new()).await;
+}
+
+#[tokio::test]
+async fn test_parquet_store_reads_back_processed_bars() {
+    check_store_read(&ParquetSink::new(temp_dir("store-parquet"))).await;
+}
+
+#[tokio::test]
+async fn test_reading_an_empty_store_keeps_the_schema() {
+    let df = MemoryBarStore::new().read("AAPL", date(2024, 1, 1)..=date(2024, 1, 31)).await.unwrap();
+
+    assert_eq!(df.get_column_names(), BAR_COLUMNS);
+    assert_eq!(df.column("time").unwrap().dtype(), &DataType::Int64);
+}
@@ -0,0 +1,56 @@
+// tests/stream_tests.rs
+
+mod common;
+
+use common::{aggs_url, bars, date, market_open, page, poly_agg_info, test_config};
+use futures::StreamExt;
+use polyextract::{
+    DateStatus, Error, FixtureTransport, MarketTimezone, MinuteExtractor, PolygonHistorySession, Processor,
+};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_extract_stream_yields_each_day() {
+    let transport = FixtureTransport::new();
+    transport.insert(&aggs_url("AAPL", "2023-12-29"), page("AAPL", Vec::new(), None));
+    for day in ["2024-01-02", "2024-01-03"] {
+        // Include an hour of pre-market bars for the processor to drop
+        transport.insert(&aggs_url("AAPL", day), page("AAPL", bars(market_open(day) - 60 * 60_000, 451), None));
+    }
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)));
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2023, 12, 29), date(2024, 1, 3)), session);
+
+    let mut batches: Vec<_> = extractor.extractor.extract_stream().map(|batch| batch.unwrap()).collect().await;
+    batches.sort_by(|a, b| a.date.cmp(&b.date));
+
+    assert_eq!(batches.len(), 3);
+    assert_eq!(batches[0].report.status, DateStatus::Empty);
+    assert_eq!(batches[0].df.height(), 0);
+
+    let market_timezone = MarketTimezone::Eastern;
+    for batch in &mut batches[1..] {
+        assert_eq!(batch.report.status, DateStatus::Succeeded);
+        assert_eq!(batch.df.column("ticker").unwrap().str().unwrap().get(0).unwrap(), "AAPL");
+        assert_eq!(batch.df.column("mkt_date").unwrap().str().unwrap().get(0).unwrap(), batch.date);
+
+        Processor::new(&mut batch.df, &market_timezone).process().unwrap();
+        assert_eq!(batch.df.height(), 391);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_extract_stream_yields_exhausted_dates_as_errors() {
+    let transport = FixtureTransport::new();
+    transport.insert(&aggs_url("AAPL", "2024-01-02"), page("AAPL", bars(market_open("2024-01-02"), 391), None));
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)));
+    let extractor = MinuteExtractor::new(poly_agg_info("AAPL", date(2024, 1, 2), date(2024, 1, 3)), session);
+
+    let results: Vec<_> = extractor.extractor.extract_stream().collect().await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().any(|result| matches!(result, Ok(batch) if batch.df.height() == 391)));
+    assert!(results.iter().any(|result| matches!(
+        result,
+        Err(Error::RetriesExhausted { context, .. }) if context.date.as_deref() == Some("2024-01-03")
+    )));
+}
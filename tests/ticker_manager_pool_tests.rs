@@ -1,6 +1,9 @@
 // tests/ticker_manager_pool_tests.rs
 
+mod common;
+
 use chrono::NaiveDate;
+use common::{cassette_session, live_session};
 use polyextract::poly_agg_info::PolyAggInfo;
 use polyextract::TickerManagerPool;
 use std::time::Instant;
@@ -23,8 +26,10 @@ async fn test_ticker_manager_pool() {
         multiplier,
     );
 
-    let ticker_manager_pool = TickerManagerPool::new(poly_agg_infos);
+    let cassette = cassette_session("pool_aapl_googl_msft_minute_2024_01_02");
+    let ticker_manager_pool = TickerManagerPool::new(poly_agg_infos, cassette.session.clone());
     let results = ticker_manager_pool.process_data_concurrently().await;
+    cassette.finish();
 
     match results {
         Ok(dataframes) => {
@@ -35,13 +40,13 @@ async fn test_ticker_manager_pool() {
 
                 // Add assertions to validate the DataFrame
                 assert_eq!(df.column("mkt_date").unwrap().str().unwrap().get(0).unwrap(), "2024-01-02");
-                assert_eq!(df.column("open").unwrap().f64().unwrap().get(0).unwrap() > 0.0, true);
-                assert_eq!(df.column("high").unwrap().f64().unwrap().get(0).unwrap() > 0.0, true);
-                assert_eq!(df.column("low").unwrap().f64().unwrap().get(0).unwrap() > 0.0, true);
-                assert_eq!(df.column("close").unwrap().f64().unwrap().get(0).unwrap() > 0.0, true);
-                assert_eq!(df.column("volume").unwrap().i64().unwrap().get(0).unwrap() > 0, true);
-                assert_eq!(df.column("vwap").unwrap().f64().unwrap().get(0).unwrap() > 0.0, true);
-                assert_eq!(df.column("transactions").unwrap().i64().unwrap().get(0).unwrap() > 0, true);
+                assert!(df.column("open").unwrap().f64().unwrap().get(0).unwrap() > 0.0);
+                assert!(df.column("high").unwrap().f64().unwrap().get(0).unwrap() > 0.0);
+                assert!(df.column("low").unwrap().f64().unwrap().get(0).unwrap() > 0.0);
+                assert!(df.column("close").unwrap().f64().unwrap().get(0).unwrap() > 0.0);
+                assert!(df.column("volume").unwrap().i64().unwrap().get(0).unwrap() > 0);
+                assert!(df.column("vwap").unwrap().f64().unwrap().get(0).unwrap() > 0.0);
+                assert!(df.column("transactions").unwrap().i64().unwrap().get(0).unwrap() > 0);
             }
         }
         Err(error) => {
@@ -56,6 +61,7 @@ async fn test_ticker_manager_pool() {
 
 
 #[tokio::test]
+#[ignore = "downloads 17 years of minute bars from Polygon; run with --ignored and POLYGON_API_KEY set"]
 async fn test_ticker_manager_pool_17_yrs() {
     let start_time = Instant::now();
 
@@ -73,7 +79,7 @@ async fn test_ticker_manager_pool_17_yrs() {
         multiplier,
     );
 
-    let ticker_manager_pool = TickerManagerPool::new(poly_agg_infos);
+    let ticker_manager_pool = TickerManagerPool::new(poly_agg_infos, live_session());
     let results = ticker_manager_pool.process_data_concurrently().await;
 
     match results {
@@ -1,11 +1,15 @@
 // tests/ticker_manager_tests.rs
 
+mod common;
+
 use chrono::NaiveDate;
+use common::live_session;
 use polyextract::poly_agg_info::PolyAggInfo;
 use polyextract::TickerManager;
 use std::time::Instant;
 
 #[tokio::test]
+#[ignore = "requires POLYGON_API_KEY"]
 async fn test_ticker_manager() {
     let start_time = Instant::now();
 
@@ -17,7 +21,7 @@ async fn test_ticker_manager() {
         multiplier: 1,
     };
 
-    let ticker_manager = TickerManager::new(poly_agg_info);
+    let ticker_manager = TickerManager::new(poly_agg_info, live_session());
     let result = ticker_manager.process_data().await;
 
     match result {
@@ -0,0 +1,140 @@
+// tests/transport_tests.rs
+
+mod common;
+
+use common::{aggs_url, bars, date, market_open, page, test_config};
+use polyextract::{
+    AggExtractor, Error, FixtureTransport, PolyAggInfo, PolygonHistorySession, ReqwestTransport, TickerManagerPool,
+    Transport, TransportResponse,
+};
+use std::sync::Arc;
+
+fn poly_agg_info(ticker: &str) -> PolyAggInfo {
+    common::poly_agg_info(ticker, date(2024, 1, 2), date(2024, 1, 2))
+}
+
+#[tokio::test]
+async fn test_fixture_transport_strips_api_key() {
+    let transport = Arc::new(FixtureTransport::new().with_response(
+        &aggs_url("AAPL", "2024-01-02"),
+        page("AAPL", bars(market_open("2024-01-02"), 1), None),
+    ));
+    let session = PolygonHistorySession::with_transport(test_config(), transport.clone());
+
+    let response = session.send_request(&aggs_url("AAPL", "2024-01-02")).await.unwrap();
+
+    assert_eq!(response.status, 200);
+    assert!(transport.requests().iter().all(|url| !url.contains("test-key")));
+}
+
+#[tokio::test]
+async fn test_reqwest_transport_errors_omit_api_key() {
+    let transport = ReqwestTransport::from_config(&test_config()).unwrap();
+
+    // Nothing listens on port 1, so the request fails before any response is read
+    let error = transport.get("http://127.0.0.1:1/v2/aggs?apiKey=test-key").await.unwrap_err();
+
+    assert!(matches!(error, Error::Transport { .. }));
+    assert!(!error.to_string().contains("apiKey"));
+    assert!(!format!("{:?}", error).contains("test-key"));
+}
+
+#[tokio::test]
+async fn test_extractor_merges_next_url_pages_offline() {
+    let cursor_url = "https://api.polygon.io/v2/aggs/ticker/AAPL/range/1/minute/2024-01-02/2024-01-02?cursor=page2";
+    let transport = FixtureTransport::new()
+        .with_response(
+            &aggs_url("AAPL", "2024-01-02"),
+            page("AAPL", bars(market_open("2024-01-02"), 200), Some(cursor_url)),
+        )
+        .with_response(cursor_url, page("AAPL", bars(market_open("2024-01-02") + 200 * 60_000, 191), None));
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)));
+
+    let df = AggExtractor::new(poly_agg_info("AAPL"), session).extractor.extract().await.unwrap();
+
+    assert_eq!(df.height(), 391);
+}
+
+#[tokio::test]
+async fn test_ticker_manager_pool_offline() {
+    let tickers = ["AAPL", "MSFT"];
+    let transport = FixtureTransport::new();
+    for ticker in tickers {
+        // Start an hour before the open so the market hours filter has something to drop
+        transport.insert(
+            &aggs_url(ticker, "2024-01-02"),
+            page(ticker, bars(market_open("2024-01-02") - 60 * 60_000, 451), None),
+        );
+    }
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(transport)));
+
+    let poly_agg_infos = tickers.iter().map(|ticker| poly_agg_info(ticker)).collect();
+    let dataframes = TickerManagerPool::new(poly_agg_infos, session)
+        .process_data_concurrently()
+        .await
+        .unwrap();
+
+    assert_eq!(dataframes.len(), 2);
+    for df in dataframes {
+        assert_eq!(df.height(), 391);
+        assert_eq!(df.column("mkt_date").unwrap().str().unwrap().get(0).unwrap(), "2024-01-02");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_unmatched_request_exhausts_retries() {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), Arc::new(FixtureTransport::new())));
+
+    let result = AggExtractor::new(poly_agg_info("AAPL"), session).extractor.extract().await;
+
+    match result {
+        Err(Error::RetriesExhausted { context, last_error, .. }) => {
+            assert_eq!(context.ticker.as_deref(), Some("AAPL"));
+            assert_eq!(context.date.as_deref(), Some("2024-01-02"));
+            assert!(matches!(*last_error, Error::Transport { .. }));
+        }
+        other => panic!("Expected retries to be exhausted, got {:?}", other.map(|df| df.height())),
+    }
+}
+
+#[tokio::test]
+async fn test_unauthorized_fails_fast() {
+    let url = aggs_url("AAPL", "2024-01-02");
+    let transport = Arc::new(FixtureTransport::new().with_response(
+        &url,
+        TransportResponse::new(403, r#"{"status":"NOT_AUTHORIZED","message":"Your plan doesn't include this data timeframe."}"#),
+    ));
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+
+    let result = AggExtractor::new(poly_agg_info("AAPL"), session).extractor.extract().await;
+
+    match result {
+        Err(Error::Unauthorized { status, message, .. }) => {
+            assert_eq!(status, 403);
+            assert!(message.contains("plan"));
+        }
+        other => panic!("Expected an unauthorized error, got {:?}", other.map(|df| df.height())),
+    }
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limited_and_error_payloads_are_retried() {
+    let url = aggs_url("AAPL", "2024-01-02");
+    let transport = Arc::new(
+        FixtureTransport::new()
+            .with_response(&url, TransportResponse::new(429, "").with_retry_after(std::time::Duration::from_secs(30)))
+            .with_response(&url, TransportResponse::new(200, r#"{"status":"ERROR","error":"Internal error"}"#))
+            .with_response(&url, TransportResponse::new(502, "Bad Gateway"))
+            .with_response(&url, page("AAPL", bars(market_open("2024-01-02"), 391), None)),
+    );
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport.clone()));
+
+    let started = tokio::time::Instant::now();
+    let df = AggExtractor::new(poly_agg_info("AAPL"), session).extractor.extract().await.unwrap();
+
+    assert_eq!(df.height(), 391);
+    assert_eq!(transport.requests().len(), 4);
+    // The first backoff honours Retry-After instead of the default two seconds
+    assert!(started.elapsed() >= std::time::Duration::from_secs(30));
+}
@@ -0,0 +1,103 @@
+// tests/update_tests.rs
+
+mod common;
+
+use chrono::{DateTime, TimeZone, Utc};
+use common::{aggs_url, bars, date, market_open, page, test_config};
+use polyextract::{
+    BarStore, ExtractOptions, FixtureTransport, MemoryBarStore, PolygonHistorySession, RetryPolicy, TransportResponse,
+    Updater,
+};
+use std::sync::Arc;
+
+/// Serves 30 bars from the open of each given date.
+fn transport(dates: &[&str]) -> Arc<FixtureTransport> {
+    let transport = FixtureTransport::new();
+    for date in dates {
+        transport.insert(&aggs_url("AAPL", date), page("AAPL", bars(market_open(date), 30), None));
+    }
+    Arc::new(transport)
+}
+
+fn updater(transport: Arc<FixtureTransport>) -> Updater {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport));
+    Updater::new(session, "minute", 1)
+        .with_first_date(date(2024, 1, 2))
+        .with_options(ExtractOptions::default().retry_policy(RetryPolicy::none()))
+}
+
+/// Returns the time of the given January 2024 day after the close.
+fn after_close(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, day, 22, 0, 0).unwrap()
+}
+
+#[tokio::test]
+async fn test_update_fetches_from_the_first_date_through_the_latest_closed_session() {
+    let transport = transport(&["2024-01-02", "2024-01-03", "2024-01-04"]);
+    let store = MemoryBarStore::new();
+
+    let report = updater(transport.clone()).update_as_of(&store, &["AAPL"], after_close(4)).await.unwrap();
+
+    assert!(report.is_complete());
+    assert_eq!(transport.requests().len(), 3);
+    assert_eq!(store.bars().height(), 90);
+    assert_eq!(store.last_date("AAPL").await.unwrap(), Some(date(2024, 1, 4)));
+}
+
+#[tokio::test]
+async fn test_update_resumes_after_the_last_stored_date() {
+    let transport = transport(&["2024-01-02", "2024-01-03", "2024-01-04", "2024-01-05", "2024-01-08"]);
+    let store = MemoryBarStore::new();
+    let updater = updater(transport.clone());
+    updater.update_as_of(&store, &["AAPL"], after_close(4)).await.unwrap();
+
+    updater.update_as_of(&store, &["AAPL"], after_close(8)).await.unwrap();
+
+    let requested = transport.requests();
+    assert_eq!(requested.len(), 5);
+    assert_eq!(requested[3], aggs_url("AAPL", "2024-01-05"));
+    assert_eq!(requested[4], aggs_url("AAPL", "2024-01-08"));
+    assert_eq!(store.bars().height(), 150);
+
+    // Nothing has closed since
+    let report = updater.update_as_of(&store, &["AAPL"], after_close(8)).await.unwrap();
+    assert!(report.reports[0].dates.is_empty());
+    assert_eq!(transport.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn test_update_deduplicates_on_ticker_and_time() {
+    let transport = transport(&["2024-01-02", "2024-01-03"]);
+    let store = MemoryBarStore::new();
+    updater(transport.clone()).update_as_of(&store, &["AAPL"], after_close(3)).await.unwrap();
+    let stored = store.bars();
+
+    store.append(&stored.slice(30, 30)).await.unwrap();
+    store.append(&stored).await.unwrap();
+
+    assert_eq!(store.bars().height(), 60);
+    assert!(store.bars().equals(&stored));
+}
+
+#[tokio::test]
+async fn test_update_stops_storing_at_the_first_failed_date() {
+    let transport = transport(&["2024-01-02", "2024-01-04"]);
+    transport.insert(&aggs_url("AAPL", "2024-01-03"), TransportResponse::new(503, "unavailable"));
+    let store = MemoryBarStore::new();
+
+    let report = updater(transport).update_as_of(&store, &["AAPL"], after_close(4)).await.unwrap();
+
+    assert_eq!(report.failed(), 1);
+    assert_eq!(store.bars().height(), 30);
+    assert_eq!(store.last_date("AAPL").await.unwrap(), Some(date(2024, 1, 2)));
+}
+
+#[tokio::test]
+async fn test_update_needs_a_first_date_for_new_tickers() {
+    let session = Arc::new(PolygonHistorySession::with_transport(test_config(), transport(&[])));
+    let store = MemoryBarStore::new();
+
+    let result = Updater::new(session, "minute", 1).update_as_of(&store, &["AAPL"], after_close(4)).await;
+
+    assert!(result.is_err());
+}